@@ -0,0 +1,218 @@
+use std::cell::RefCell;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Cursor, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use zip::ZipArchive;
+
+///
+/// Separates the path of an archive from the path of a file inside it, like
+/// `src.zip!/java.base/java/util/List.java`
+///
+pub(crate) const SEPARATOR: &str = "!/";
+
+///
+/// Extensions of the archives the files are read from
+///
+const ARCHIVES: &[&str] = &["zip", "jar"];
+
+thread_local! {
+    ///
+    /// The last archive read in the thread, kept open as reading the list of
+    /// its files again for every file takes long with thousands of them
+    ///
+    static LAST: RefCell<Option<Opened>> = const { RefCell::new(None) };
+}
+
+///
+/// An archive with the modification time and size it had when opened
+///
+struct Opened {
+    path: String,
+    stamp: (Option<SystemTime>, u64),
+    zip: ZipArchive<File>,
+}
+
+///
+/// A file on disk or inside an archive
+///
+pub(crate) enum Reader {
+    File(BufReader<File>),
+    Entry(Cursor<Vec<u8>>),
+}
+
+///
+/// Opens the file, or the file inside the archive if the path has one
+///
+pub(crate) fn open(path: &str) -> io::Result<Reader> {
+    match split(path) {
+        Some((archive, entry)) => Ok(Reader::Entry(Cursor::new(read(archive, entry)?))),
+        None => Ok(Reader::File(BufReader::new(File::open(path)?))),
+    }
+}
+
+///
+/// The file on disk the path is in, the archive if the file is inside one
+///
+pub(crate) fn outer(path: &str) -> &str {
+    split(path).map_or(path, |(archive, _)| archive)
+}
+
+///
+/// Whether the file is an archive the files can be read from
+///
+pub(crate) fn is_archive(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ARCHIVES.iter().any(|a| ext.eq_ignore_ascii_case(a)))
+}
+
+///
+/// The paths of the files inside the archive with one of the extensions
+///
+pub(crate) fn entries(archive: &Path, extensions: &[String]) -> io::Result<Vec<PathBuf>> {
+    let zip = ZipArchive::new(File::open(archive)?).map_err(io::Error::other)?;
+    Ok(zip
+        .file_names()
+        .filter(|name| {
+            Path::new(name)
+                .extension()
+                .is_some_and(|ext| extensions.iter().any(|e| ext == e.as_str()))
+        })
+        .map(|name| PathBuf::from(format!("{}{SEPARATOR}{name}", archive.display())))
+        .collect())
+}
+
+///
+/// The contents of the file inside the archive, opening the archive again
+/// only if it is not the last one read in the thread or it changed since
+///
+fn read(archive: &str, entry: &str) -> io::Result<Vec<u8>> {
+    let metadata = fs::metadata(archive)?;
+    let stamp = (metadata.modified().ok(), metadata.len());
+    LAST.with_borrow_mut(|last| {
+        let mut opened = match last.take() {
+            Some(opened) if opened.path == archive && opened.stamp == stamp => opened,
+            _ => Opened {
+                path: archive.to_string(),
+                stamp,
+                zip: ZipArchive::new(File::open(archive)?).map_err(io::Error::other)?,
+            },
+        };
+        let mut contents = Vec::new();
+        let read = match opened.zip.by_name(entry) {
+            Ok(mut file) => file.read_to_end(&mut contents),
+            Err(e) => Err(io::Error::other(e)),
+        };
+        *last = Some(opened);
+        read.map(|_| contents)
+    })
+}
+
+fn split(path: &str) -> Option<(&str, &str)> {
+    path.split_once(SEPARATOR)
+        .filter(|(archive, _)| is_archive(Path::new(archive)))
+}
+
+impl Read for Reader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Reader::File(file) => file.read(buf),
+            Reader::Entry(entry) => entry.read(buf),
+        }
+    }
+}
+
+impl BufRead for Reader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        match self {
+            Reader::File(file) => file.fill_buf(),
+            Reader::Entry(entry) => entry.fill_buf(),
+        }
+    }
+
+    fn consume(&mut self, amount: usize) {
+        match self {
+            Reader::File(file) => file.consume(amount),
+            Reader::Entry(entry) => entry.consume(amount),
+        }
+    }
+}
+
+impl Seek for Reader {
+    fn seek(&mut self, position: SeekFrom) -> io::Result<u64> {
+        match self {
+            Reader::File(file) => file.seek(position),
+            Reader::Entry(entry) => entry.seek(position),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::TempFolder;
+    use crate::{get_random_code_line, Language, LineConfig, Sampling};
+    use std::io::Write;
+    use zip::write::{SimpleFileOptions, ZipWriter};
+
+    #[test]
+    fn test_archive_entries() {
+        let folder = TempFolder::new("archive");
+        let archive = folder.join("thing-sources.jar");
+        let mut zip = ZipWriter::new(File::create(&archive).unwrap());
+        zip.start_file("thing/Thing.java", SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b"class Thing {\n    int value = 1 + 1;\n}\n")
+            .unwrap();
+        zip.start_file("META-INF/MANIFEST.MF", SimpleFileOptions::default())
+            .unwrap();
+        zip.finish().unwrap();
+
+        let entries = entries(&archive, &[String::from("java")]).unwrap();
+        let path = format!("{}!/thing/Thing.java", archive.display());
+        assert_eq!(entries, vec![PathBuf::from(&path)]);
+        assert_eq!(outer(&path), archive.display().to_string());
+
+        let mut lines = open(&path).unwrap().lines();
+        assert_eq!(lines.nth(1).unwrap().unwrap(), "    int value = 1 + 1;");
+        assert!(open(&format!("{}!/Other.java", archive.display())).is_err());
+    }
+
+    #[test]
+    fn test_archive_many_entries() {
+        let folder = TempFolder::new("archive-many");
+        let archive = folder.join("big-sources.jar");
+        let mut zip = ZipWriter::new(File::create(&archive).unwrap());
+        for i in 0..20_000 {
+            zip.start_file(format!("big/Thing{i}.java"), SimpleFileOptions::default())
+                .unwrap();
+            writeln!(zip, "int value = {i} + {i};").unwrap();
+        }
+        zip.finish().unwrap();
+
+        let config = LineConfig::builder()
+            .language(Language::Java)
+            .source(&archive.display().to_string())
+            .sampling(Sampling::Lines)
+            .cache_folder(&folder.join("cache").display().to_string())
+            .build()
+            .unwrap();
+        assert_eq!(config.paths().unwrap().len(), 20_000);
+        let line = get_random_code_line(&config).unwrap();
+        assert!(line.text.starts_with("int value = "));
+        assert!(line
+            .path
+            .starts_with(&format!("{}!/big/", archive.display())));
+
+        let mut zip = ZipWriter::new(File::create(&archive).unwrap());
+        zip.start_file("big/Thing0.java", SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b"class Thing0 {\n    int changed = 0 + 0;\n}\n")
+            .unwrap();
+        zip.finish().unwrap();
+        let path = format!("{}!/big/Thing0.java", archive.display());
+        let mut lines = open(&path).unwrap().lines();
+        assert_eq!(lines.nth(1).unwrap().unwrap(), "    int changed = 0 + 0;");
+    }
+}
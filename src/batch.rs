@@ -0,0 +1,168 @@
+use rand::seq::SliceRandom;
+use std::collections::{HashMap, HashSet};
+
+use crate::archive;
+use crate::sampling::Candidates;
+use crate::{get_lines_from_file, CodeLine, LineConfig, LinesError, LinesResult};
+
+///
+/// Returns `n` distinct random lines of code that match the config argument
+/// It returns a [`LinesError`] if something bad happens, or if there are not
+/// `n` distinct lines to return
+///
+/// The files are only searched once, and each line is picked like in
+/// [`get_random_code_line`](crate::get_random_code_line), following the
+/// [`sampling`](crate::LineConfigBuilder::sampling), but never returning the same text twice. With
+/// [`distinct_files`](crate::LineConfigBuilder::distinct_files) each line is
+/// from a different file.
+///
+/// # Arguments
+///
+/// * `config` - A reference to a [`LineConfig`]
+/// * `n` - The number of lines
+///
+pub fn get_random_lines(config: &LineConfig, n: usize) -> LinesResult<Vec<CodeLine>> {
+    let mut sampler = Sampler::new(config, Candidates::new(config, config.paths()?));
+    let mut lines = Vec::with_capacity(n);
+    while lines.len() < n {
+        match sampler.next_line() {
+            Some(line) => lines.push(line),
+            None => {
+                return Err(LinesError::NotEnoughLines {
+                    requested: n,
+                    found: lines.len(),
+                })
+            }
+        }
+    }
+    Ok(lines)
+}
+
+///
+/// Picks random lines from the files without repeating them
+///
+pub(crate) struct Sampler<'a> {
+    config: &'a LineConfig,
+    candidates: Candidates,
+    files: HashMap<String, SampledFile>,
+    returned: HashSet<String>,
+}
+
+///
+/// The lines of a file and its eligible lines not picked yet, shuffled
+///
+struct SampledFile {
+    lines: Vec<String>,
+    remaining: Vec<usize>,
+}
+
+impl<'a> Sampler<'a> {
+    pub(crate) fn new(config: &'a LineConfig, candidates: Candidates) -> Self {
+        Sampler {
+            config,
+            candidates,
+            files: HashMap::new(),
+            returned: HashSet::new(),
+        }
+    }
+
+    ///
+    /// The next random line, or none once all the lines have been returned
+    ///
+    pub(crate) fn next_line(&mut self) -> Option<CodeLine> {
+        while let Some(position) = self.candidates.pick(self.config) {
+            let path = self.candidates.path(position).to_string();
+            let file = self
+                .files
+                .entry(path.clone())
+                .or_insert_with(|| SampledFile::read(&path, self.config));
+            let Some(index) = file.remaining.pop() else {
+                self.files.remove(&self.candidates.remove(position));
+                continue;
+            };
+            self.candidates.set_count(position, file.remaining.len());
+            let line = CodeLine::new(&file.lines[index], &path, index, self.config);
+            if !self.returned.insert(line.text.clone()) {
+                continue;
+            }
+            if self.config.distinct_files() {
+                self.files.remove(&self.candidates.remove(position));
+            }
+            return Some(line);
+        }
+        None
+    }
+}
+
+impl SampledFile {
+    fn read(path: &str, config: &LineConfig) -> Self {
+        let lines = archive::open(path)
+            .map(get_lines_from_file)
+            .unwrap_or_default();
+        let mut remaining = config.eligible_lines(&lines);
+        remaining.shuffle(&mut *config.rng());
+        SampledFile { lines, remaining }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::TempFolder;
+
+    fn get_paths(folder: &TempFolder) -> Vec<String> {
+        vec![
+            folder.write(
+                "a.rs",
+                "let first = 1 + 1;\nlet second = 2 + 2;\nlet first = 1 + 1;\n",
+            ),
+            folder.write("b.rs", "// only a comment here\n"),
+            folder.write("c.rs", "let third = 3 + 3;\n"),
+        ]
+    }
+
+    #[test]
+    fn test_sampler_distinct_lines() {
+        let folder = TempFolder::new("batch-lines");
+        let paths = get_paths(&folder);
+        let config = LineConfig::default();
+        let mut sampler = Sampler::new(&config, Candidates::new(&config, paths.clone()));
+        let mut lines: Vec<String> = std::iter::from_fn(|| sampler.next_line())
+            .map(|l| l.text)
+            .collect();
+        lines.sort();
+        assert_eq!(
+            lines,
+            vec![
+                "let first = 1 + 1;",
+                "let second = 2 + 2;",
+                "let third = 3 + 3;"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sampler_distinct_files() {
+        let folder = TempFolder::new("batch-files");
+        let paths = get_paths(&folder);
+        let config = LineConfig::builder().distinct_files(true).build().unwrap();
+        let mut sampler = Sampler::new(&config, Candidates::new(&config, paths.clone()));
+        let lines: Vec<CodeLine> = std::iter::from_fn(|| sampler.next_line()).collect();
+        assert_eq!(lines.len(), 2);
+        assert_ne!(lines[0].path, lines[1].path);
+    }
+
+    #[test]
+    fn test_sampler_seed() {
+        let folder = TempFolder::new("batch-seed");
+        let paths = get_paths(&folder);
+        let sample = |seed| -> Vec<String> {
+            let config = LineConfig::builder().seed(seed).build().unwrap();
+            let mut sampler = Sampler::new(&config, Candidates::new(&config, paths.clone()));
+            std::iter::from_fn(|| sampler.next_line())
+                .map(|l| l.text)
+                .collect()
+        };
+        assert_eq!(sample(3), sample(3));
+    }
+}
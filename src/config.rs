@@ -0,0 +1,727 @@
+use rand::rngs::StdRng;
+use rand::{thread_rng, Rng, RngCore, SeedableRng};
+use regex::Regex;
+use std::collections::HashSet;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::index::{cache_folder, Index};
+use crate::root::Search;
+use crate::{Language, LinesError, LinesResult, Sampling, SourceRoot};
+
+///
+/// Number of bytes the lines have to exceed, indentation included, when no
+/// minimum length is set
+///
+const DEFAULT_MIN_BYTES: usize = 10;
+
+///
+/// Configuration of the requested lines
+///
+/// Build it with [`LineConfig::builder`], or [`LineConfig::new`] to only set
+/// the language.
+///
+#[derive(Debug, Clone)]
+pub struct LineConfig {
+    pub(crate) language: Language,
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+    max_width: Option<usize>,
+    trim: bool,
+    ascii_only: bool,
+    include: Vec<Regex>,
+    exclude: Vec<Regex>,
+    sources: Vec<String>,
+    roots: Vec<SourceRoot>,
+    search: Search,
+    distinct_files: bool,
+    sampling: Sampling,
+    seed: Option<u64>,
+    rng: SharedRng,
+    cache: Option<SharedIndex>,
+    threads: Option<usize>,
+    retries: usize,
+    exhausted: Arc<Mutex<HashSet<String>>>,
+}
+
+impl LineConfig {
+    ///
+    /// Configuration for the lines of the language, with the default options
+    ///
+    pub fn new(language: Language) -> Self {
+        let seed = thread_rng().gen();
+        LineConfig {
+            language,
+            min_length: None,
+            max_length: None,
+            max_width: None,
+            trim: true,
+            ascii_only: false,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            sources: Vec::new(),
+            roots: Vec::new(),
+            search: Search::default(),
+            distinct_files: false,
+            sampling: Sampling::Files,
+            seed: Some(seed),
+            rng: SharedRng::new(StdRng::seed_from_u64(seed)),
+            cache: None,
+            threads: None,
+            retries: 10,
+            exhausted: Arc::default(),
+        }
+    }
+
+    ///
+    /// Builder of a configuration, for Rust lines with the default options
+    ///
+    pub fn builder() -> LineConfigBuilder {
+        LineConfigBuilder {
+            config: LineConfig::default(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            exclude_files: Vec::new(),
+        }
+    }
+
+    ///
+    /// The language that you want the lines from
+    ///
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    ///
+    /// Whether the lines are returned without their indentation
+    ///
+    pub fn trim(&self) -> bool {
+        self.trim
+    }
+
+    ///
+    /// Whether the lines of [`get_random_lines`](crate::get_random_lines) are
+    /// from different files
+    ///
+    pub fn distinct_files(&self) -> bool {
+        self.distinct_files
+    }
+
+    ///
+    /// How the random lines are picked
+    ///
+    pub fn sampling(&self) -> Sampling {
+        self.sampling
+    }
+
+    ///
+    /// The number of threads the files are read with, all the cores if none
+    ///
+    pub fn threads(&self) -> Option<usize> {
+        self.threads
+    }
+
+    ///
+    /// The number of random files tried when they have no lines, before
+    /// trying all the others
+    ///
+    pub fn retries(&self) -> usize {
+        self.retries
+    }
+
+    ///
+    /// The seed of the random numbers, none if they come from a custom [`Rng`]
+    ///
+    /// A configuration built with the same seed returns the same lines from
+    /// the same files, as long as the files don't change.
+    ///
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    ///
+    /// Keeps the lines the language and the configuration accept, as they
+    /// would be returned
+    ///
+    pub fn filter_lines(&self, lines: Vec<String>) -> Vec<String> {
+        self.eligible_lines(&lines)
+            .into_iter()
+            .map(|i| self.text(&lines[i]).to_string())
+            .collect()
+    }
+
+    ///
+    /// Indexes of the lines the language and the configuration accept
+    ///
+    pub(crate) fn eligible_lines(&self, lines: &[String]) -> Vec<usize> {
+        self.language
+            .spec()
+            .eligible_lines(lines)
+            .into_iter()
+            .filter(|&i| self.accepts(&lines[i]))
+            .collect()
+    }
+
+    ///
+    /// Tells which lines the language and the configuration accept, one line
+    /// at a time
+    ///
+    pub(crate) fn line_filter(&self) -> impl FnMut(&str) -> bool + '_ {
+        let mut language = self.language.spec().line_filter();
+        move |line| language(line) && self.accepts(line)
+    }
+
+    ///
+    /// The paths of the files the lines are taken from
+    ///
+    pub(crate) fn paths(&self) -> LinesResult<Vec<String>> {
+        self.cached(|index| Ok(index.paths()))
+            .unwrap_or_else(|| self.globbed_paths())
+    }
+
+    ///
+    /// The paths of the files the lines are taken from, searched again
+    ///
+    pub(crate) fn globbed_paths(&self) -> LinesResult<Vec<String>> {
+        if self.sources.is_empty() && self.roots.is_empty() {
+            self.language.get_paths(&self.search)
+        } else {
+            self.language
+                .get_paths_in(&self.sources, &self.roots, &self.search)
+        }
+    }
+
+    ///
+    /// The globs of the files the lines are taken from
+    ///
+    pub(crate) fn globs(&self) -> Vec<String> {
+        if self.sources.is_empty() && self.roots.is_empty() {
+            return self.language.globs();
+        }
+        let mut globs = self.sources.clone();
+        for root in &self.roots {
+            globs.extend(root.globs(self.language));
+        }
+        globs
+    }
+
+    ///
+    /// Whether the ignore files in the root of its glob leave the file out
+    ///
+    #[cfg(feature = "watch")]
+    pub(crate) fn ignores(&self, root: &std::path::Path, path: &std::path::Path) -> bool {
+        self.search.ignores(root, path)
+    }
+
+    ///
+    /// Whether one of the globs of the files left out or of the roots leaves
+    /// the file out
+    ///
+    #[cfg(feature = "watch")]
+    pub(crate) fn excludes(&self, path: &std::path::Path) -> bool {
+        self.search.excludes(path) || self.roots.iter().any(|root| root.excludes(path))
+    }
+
+    ///
+    /// Runs the function with the index of the configuration, loading it
+    /// first, none if the configuration has no cache
+    ///
+    pub(crate) fn cached<T>(
+        &self,
+        f: impl FnOnce(&mut Index) -> LinesResult<T>,
+    ) -> Option<LinesResult<T>> {
+        let cache = self.cache.as_ref()?;
+        let mut index = cache.index.lock().unwrap_or_else(PoisonError::into_inner);
+        if index.is_none() {
+            match Index::open(self, &cache.folder) {
+                Ok(opened) => *index = Some(opened),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        index.as_mut().map(f)
+    }
+
+    ///
+    /// What the index of the configuration depends on
+    ///
+    pub(crate) fn cache_key(&self) -> String {
+        let globs = self.globs();
+        let excluded: Vec<(&str, &[String])> = self
+            .roots
+            .iter()
+            .map(|root| (root.folder(), root.exclude_globs()))
+            .collect();
+        let patterns = |regexes: &[Regex]| -> Vec<String> {
+            regexes.iter().map(|r| r.as_str().to_string()).collect()
+        };
+        let spec = self.language.spec();
+        format!(
+            "{} {}\n{:?}\n{globs:?} {excluded:?} {}\n{:?} {:?} {:?} {} {}\n{:?}\n{:?}",
+            self.language,
+            spec.cache_key(),
+            spec.syntax(),
+            self.search.key(),
+            self.min_length,
+            self.max_length,
+            self.max_width,
+            self.trim,
+            self.ascii_only,
+            patterns(&self.include),
+            patterns(&self.exclude)
+        )
+    }
+
+    ///
+    /// Remembers that the file has no lines, shared by the clones of the
+    /// configuration
+    ///
+    pub(crate) fn exhaust(&self, path: &str) {
+        self.exhausted
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(path.to_string());
+    }
+
+    ///
+    /// Removes the files known to have no lines
+    ///
+    pub(crate) fn skip_exhausted(&self, paths: &mut Vec<String>) {
+        let exhausted = self
+            .exhausted
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if !exhausted.is_empty() {
+            paths.retain(|path| !exhausted.contains(path));
+        }
+    }
+
+    ///
+    /// The random number generator, shared by the clones of the configuration
+    ///
+    pub(crate) fn rng(&self) -> MutexGuard<'_, Box<dyn RngCore + Send>> {
+        self.rng.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    ///
+    /// The line as it is returned
+    ///
+    pub(crate) fn text<'a>(&self, line: &'a str) -> &'a str {
+        if self.trim {
+            line.trim()
+        } else {
+            line.trim_end()
+        }
+    }
+
+    fn accepts(&self, line: &str) -> bool {
+        let text = self.text(line);
+        let length = text.chars().count();
+        let long_enough = match self.min_length {
+            Some(min) => length >= min,
+            None => line.len() > DEFAULT_MIN_BYTES,
+        };
+        long_enough
+            && self.max_length.is_none_or(|max| length <= max)
+            && self.max_width.is_none_or(|max| width(text) <= max)
+            && (!self.ascii_only || text.is_ascii())
+            && (self.include.is_empty() || self.include.iter().any(|r| r.is_match(text)))
+            && !self.exclude.iter().any(|r| r.is_match(text))
+    }
+}
+
+impl Default for LineConfig {
+    fn default() -> Self {
+        LineConfig::new(Language::Rust)
+    }
+}
+
+///
+/// Random number generator that can be shared between threads and clones
+///
+#[derive(Clone)]
+struct SharedRng(Arc<Mutex<Box<dyn RngCore + Send>>>);
+
+impl SharedRng {
+    fn new(rng: impl RngCore + Send + 'static) -> Self {
+        SharedRng(Arc::new(Mutex::new(Box::new(rng))))
+    }
+}
+
+impl fmt::Debug for SharedRng {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("SharedRng")
+    }
+}
+
+///
+/// Index on disk shared between the clones of the configuration, loaded when
+/// it is first needed
+///
+#[derive(Clone)]
+struct SharedIndex {
+    folder: PathBuf,
+    index: Arc<Mutex<Option<Index>>>,
+}
+
+impl SharedIndex {
+    fn new(folder: PathBuf) -> Self {
+        SharedIndex {
+            folder,
+            index: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+impl fmt::Debug for SharedIndex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SharedIndex({})", self.folder.display())
+    }
+}
+
+///
+/// Columns the text takes, with tabs of 4 columns
+///
+fn width(text: &str) -> usize {
+    text.chars().map(|c| if c == '\t' { 4 } else { 1 }).sum()
+}
+
+///
+/// Builder of a [`LineConfig`]
+///
+/// ```
+/// use code_lines::{Language, LineConfig};
+///
+/// let config = LineConfig::builder()
+///     .language(Language::Java)
+///     .min_length(20)
+///     .max_width(80)
+///     .exclude(r"^\s*(public|private)")
+///     .build()
+///     .unwrap();
+/// ```
+///
+#[derive(Debug)]
+pub struct LineConfigBuilder {
+    config: LineConfig,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    exclude_files: Vec<String>,
+}
+
+impl LineConfigBuilder {
+    ///
+    /// The language that you want the lines from, Rust by default
+    ///
+    pub fn language(mut self, language: Language) -> Self {
+        self.config.language = language;
+        self
+    }
+
+    ///
+    /// Minimum number of characters of the lines
+    ///
+    /// By default the lines need more than 10 bytes, counting their
+    /// indentation even if it is removed.
+    ///
+    pub fn min_length(mut self, min_length: usize) -> Self {
+        self.config.min_length = Some(min_length);
+        self
+    }
+
+    ///
+    /// Maximum number of characters of the lines, none by default
+    ///
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.config.max_length = Some(max_length);
+        self
+    }
+
+    ///
+    /// Maximum number of columns the lines take, with tabs of 4 columns, none
+    /// by default
+    ///
+    pub fn max_width(mut self, max_width: usize) -> Self {
+        self.config.max_width = Some(max_width);
+        self
+    }
+
+    ///
+    /// Whether to remove the indentation of the lines, true by default
+    ///
+    /// The trailing whitespace is always removed. The length and the width are
+    /// those of the lines as returned.
+    ///
+    pub fn trim(mut self, trim: bool) -> Self {
+        self.config.trim = trim;
+        self
+    }
+
+    ///
+    /// Whether to only return lines with ASCII characters, false by default
+    ///
+    pub fn ascii_only(mut self, ascii_only: bool) -> Self {
+        self.config.ascii_only = ascii_only;
+        self
+    }
+
+    ///
+    /// Regex the lines have to match, if several are given any of them
+    ///
+    pub fn include(mut self, regex: &str) -> Self {
+        self.include.push(regex.to_string());
+        self
+    }
+
+    ///
+    /// Regex the lines must not match
+    ///
+    pub fn exclude(mut self, regex: &str) -> Self {
+        self.exclude.push(regex.to_string());
+        self
+    }
+
+    ///
+    /// Glob of the files to take the lines from, instead of the ones in the
+    /// environment variable or the default folders of the language
+    ///
+    pub fn source(mut self, glob: &str) -> Self {
+        self.config.sources.push(glob.to_string());
+        self
+    }
+
+    ///
+    /// Folder to take the files from, with its own globs, instead of the ones
+    /// in the environment variable or the default folders of the language
+    ///
+    /// The files of all the roots and sources are merged.
+    ///
+    pub fn root(mut self, root: SourceRoot) -> Self {
+        self.config.roots.push(root);
+        self
+    }
+
+    ///
+    /// Glob of the files to leave out, like `**/target/**`, whichever source
+    /// or root they are in
+    ///
+    pub fn exclude_files(mut self, glob: &str) -> Self {
+        self.exclude_files.push(glob.to_string());
+        self
+    }
+
+    ///
+    /// Whether to leave out the files listed in the `.gitignore` and
+    /// `.ignore` files of their folders, true by default
+    ///
+    /// The folders of the globs are walked then, reading the ignore files
+    /// inside them.
+    ///
+    pub fn ignore_files(mut self, ignore_files: bool) -> Self {
+        self.config.search.set_ignore_files(ignore_files);
+        self
+    }
+
+    ///
+    /// Whether the lines of [`get_random_lines`](crate::get_random_lines) have
+    /// to be from different files, false by default
+    ///
+    pub fn distinct_files(mut self, distinct_files: bool) -> Self {
+        self.config.distinct_files = distinct_files;
+        self
+    }
+
+    ///
+    /// How the random lines are picked, one file and then one of its lines by
+    /// default
+    ///
+    pub fn sampling(mut self, sampling: Sampling) -> Self {
+        self.config.sampling = sampling;
+        self
+    }
+
+    ///
+    /// Whether to keep an index of the files and their lines on disk, in
+    /// `$XDG_CACHE_HOME/code-lines`, false by default
+    ///
+    /// The files are then searched only once, new files are not found until
+    /// the index is deleted. A file that changed is read again when it is
+    /// picked.
+    ///
+    pub fn cache(mut self, cache: bool) -> Self {
+        self.config.cache = cache.then(|| SharedIndex::new(cache_folder()));
+        self
+    }
+
+    ///
+    /// Keeps the index of the files and their lines in the folder, see
+    /// [`LineConfigBuilder::cache`]
+    ///
+    pub fn cache_folder(mut self, folder: &str) -> Self {
+        self.config.cache = Some(SharedIndex::new(PathBuf::from(folder)));
+        self
+    }
+
+    ///
+    /// Number of threads to read all the files with, to build the index or
+    /// count their lines, one per core by default
+    ///
+    /// The files are only read in parallel with the `parallel` feature.
+    ///
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.config.threads = Some(threads);
+        self
+    }
+
+    ///
+    /// Number of random files to try when the ones picked have no lines,
+    /// before trying all the others in turn, 10 by default
+    ///
+    pub fn retries(mut self, retries: usize) -> Self {
+        self.config.retries = retries;
+        self
+    }
+
+    ///
+    /// Seed of the random numbers, to get the same lines again, a random one
+    /// by default
+    ///
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.seed = Some(seed);
+        self.config.rng = SharedRng::new(StdRng::seed_from_u64(seed));
+        self
+    }
+
+    ///
+    /// Random number generator to pick the files and lines with, instead of
+    /// one with a seed
+    ///
+    pub fn rng(mut self, rng: impl Rng + Send + 'static) -> Self {
+        self.config.seed = None;
+        self.config.rng = SharedRng::new(rng);
+        self
+    }
+
+    ///
+    /// Builds the configuration
+    /// It returns a [`LinesError`] if a regex or a glob of the files left out
+    /// is not valid
+    ///
+    pub fn build(mut self) -> LinesResult<LineConfig> {
+        for glob in &self.exclude_files {
+            self.config.search.exclude(glob)?;
+        }
+        Ok(LineConfig {
+            include: compile(&self.include)?,
+            exclude: compile(&self.exclude)?,
+            roots: self
+                .config
+                .roots
+                .into_iter()
+                .map(SourceRoot::compile)
+                .collect::<LinesResult<_>>()?,
+            ..self.config
+        })
+    }
+}
+
+fn compile(regexes: &[String]) -> LinesResult<Vec<Regex>> {
+    regexes
+        .iter()
+        .map(|r| {
+            Regex::new(r).map_err(|source| LinesError::InvalidRegex {
+                pattern: r.clone(),
+                source,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_lines() -> Vec<String> {
+        vec![
+            "    let half = value / 2;".to_string(),
+            "    let name = \"café\";".to_string(),
+            "\t\tlet a = 1;".to_string(),
+            "    }".to_string(),
+        ]
+    }
+
+    #[test]
+    fn test_line_config_default() {
+        assert_eq!(
+            LineConfig::default().filter_lines(get_lines()),
+            vec![
+                "let half = value / 2;",
+                "let name = \"café\";",
+                "let a = 1;"
+            ]
+        );
+        let config = LineConfig::builder().min_length(11).build().unwrap();
+        assert_eq!(
+            config.filter_lines(get_lines()),
+            vec!["let half = value / 2;", "let name = \"café\";"]
+        );
+    }
+
+    #[test]
+    fn test_line_config_builder() {
+        let config = LineConfig::builder()
+            .min_length(0)
+            .ascii_only(true)
+            .trim(false)
+            .max_width(24)
+            .exclude("half")
+            .build()
+            .unwrap();
+        assert_eq!(
+            config.filter_lines(get_lines()),
+            vec!["\t\tlet a = 1;", "    }"]
+        );
+    }
+
+    #[test]
+    fn test_line_config_include() {
+        let config = LineConfig::builder()
+            .include(r"^let \w+ = \d")
+            .include("café")
+            .min_length(5)
+            .max_length(20)
+            .build()
+            .unwrap();
+        assert_eq!(
+            config.filter_lines(get_lines()),
+            vec!["let name = \"café\";", "let a = 1;"]
+        );
+    }
+
+    #[test]
+    fn test_line_config_seed() {
+        let numbers =
+            |config: LineConfig| -> Vec<u32> { (0..5).map(|_| config.rng().gen()).collect() };
+        let config = LineConfig::builder().seed(7).build().unwrap();
+        assert_eq!(config.seed(), Some(7));
+        assert_eq!(
+            numbers(config),
+            numbers(LineConfig::builder().seed(7).build().unwrap())
+        );
+
+        let config = LineConfig::default();
+        let seed = config.seed().unwrap();
+        assert_eq!(
+            numbers(config),
+            numbers(LineConfig::builder().seed(seed).build().unwrap())
+        );
+
+        let config = LineConfig::builder().rng(StdRng::seed_from_u64(7)).build();
+        assert_eq!(config.unwrap().seed(), None);
+    }
+
+    #[test]
+    fn test_line_config_invalid_regex() {
+        assert!(LineConfig::builder().include("(").build().is_err());
+    }
+}
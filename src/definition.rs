@@ -0,0 +1,360 @@
+use serde::de::Error as _;
+use serde::Deserialize;
+use std::{env, fs, path::Path};
+
+use crate::lexer::{Lexer, LineKind, RawStrings, StringLiteral, Syntax};
+use crate::{Language, LanguageSpec, LineFilter, LinesError, LinesResult};
+
+///
+/// A language described in a TOML file
+///
+/// ```toml
+/// name = "Kotlin"
+/// aliases = ["kt"]
+/// extensions = ["kt", "kts"]
+/// sources = ["~/projects/**/*.kt"]
+/// env_var = "KOTLIN_LINES"
+/// line_comments = ["//"]
+/// block_comments = [["/*", "*/"]]
+/// nested_comments = true
+/// raw_strings = "none"
+/// item_prefixes = ["fun ", "class "]
+/// manifests = ["build.gradle.kts"]
+///
+/// [[strings]]
+/// delimiter = '"""'
+/// escape = '\'
+/// multiline = true
+///
+/// [[strings]]
+/// delimiter = '"'
+/// escape = '\'
+///
+/// [filter]
+/// min_length = 20
+/// exclude_prefixes = ["import ", "package "]
+/// ```
+///
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LanguageDefinition {
+    ///
+    /// The name of the language
+    ///
+    pub name: String,
+    ///
+    /// Other names the language can be looked up with
+    ///
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    ///
+    /// Extensions of the source files, without the dot
+    ///
+    pub extensions: Vec<String>,
+    ///
+    /// Globs of the default source files, a leading `~` is the home folder
+    ///
+    #[serde(default)]
+    pub sources: Vec<String>,
+    ///
+    /// Environment variable with the folder to take the source files from
+    ///
+    #[serde(default)]
+    pub env_var: Option<String>,
+    ///
+    /// Markers starting a comment that lasts until the end of the line
+    ///
+    #[serde(default)]
+    pub line_comments: Vec<String>,
+    ///
+    /// Pairs of markers opening and closing a block comment
+    ///
+    #[serde(default)]
+    pub block_comments: Vec<(String, String)>,
+    ///
+    /// Whether block comments can be nested
+    ///
+    #[serde(default)]
+    pub nested_comments: bool,
+    ///
+    /// The string literals, by default `"` escaped with `\`
+    ///
+    #[serde(default = "default_strings")]
+    pub strings: Vec<StringLiteral>,
+    ///
+    /// Letters that can go right before a string and are part of it
+    ///
+    #[serde(default)]
+    pub string_prefixes: Vec<char>,
+    ///
+    /// Whether `'` only starts a literal when it is a char, see [`Syntax`]
+    ///
+    #[serde(default)]
+    pub char_literals: bool,
+    ///
+    /// Whether `/` can start a regex literal, see [`Syntax`]
+    ///
+    #[serde(default)]
+    pub regex_literals: bool,
+    ///
+    /// The style of the raw strings: `"none"`, `"hashed"` or `"delimited"`
+    ///
+    #[serde(default)]
+    pub raw_strings: RawStrings,
+    ///
+    /// Starts of the lines, once trimmed, that open an item between braces,
+    /// like `fun ` or `class `
+    ///
+    #[serde(default)]
+    pub item_prefixes: Vec<String>,
+    ///
+    /// Names of the files at the root of a package, like `build.gradle.kts`
+    ///
+    #[serde(default)]
+    pub manifests: Vec<String>,
+    ///
+    /// Rules the lines have to pass
+    ///
+    #[serde(default)]
+    pub filter: FilterRules,
+}
+
+///
+/// Rules the lines of a [`LanguageDefinition`] have to pass
+///
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FilterRules {
+    ///
+    /// Minimum length of the line, indentation included, on top of the one of
+    /// the [`LineConfig`](crate::LineConfig)
+    ///
+    pub min_length: usize,
+    ///
+    /// Lines starting with any of these, once trimmed, are skipped
+    ///
+    pub exclude_prefixes: Vec<String>,
+    ///
+    /// Lines containing any of these are skipped
+    ///
+    pub exclude_containing: Vec<String>,
+}
+
+impl LanguageDefinition {
+    ///
+    /// Parses a definition from the contents of a TOML file
+    ///
+    pub fn from_toml(toml: &str) -> LinesResult<Self> {
+        toml::from_str(toml)
+            .and_then(LanguageDefinition::checked)
+            .map_err(|source| LinesError::InvalidDefinition { path: None, source })
+    }
+
+    ///
+    /// Reads and parses the definition in the TOML file
+    ///
+    pub fn from_file<P: AsRef<Path>>(path: P) -> LinesResult<Self> {
+        let path = path.as_ref();
+        let path_name = path.display().to_string();
+        let toml = fs::read_to_string(path).map_err(|e| LinesError::io(&path_name, e))?;
+        toml::from_str(&toml)
+            .and_then(LanguageDefinition::checked)
+            .map_err(|source| LinesError::InvalidDefinition {
+                path: Some(path_name),
+                source,
+            })
+    }
+
+    ///
+    /// Checks that no comment or string marker is empty, as the lines can't
+    /// be split with an empty marker
+    ///
+    fn checked(self) -> Result<Self, toml::de::Error> {
+        let empty = self
+            .line_comments
+            .iter()
+            .chain(
+                self.block_comments
+                    .iter()
+                    .flat_map(|(open, close)| [open, close]),
+            )
+            .chain(self.strings.iter().map(|s| &s.delimiter))
+            .any(String::is_empty);
+        if empty {
+            return Err(toml::de::Error::custom(
+                "comment and string markers can't be empty",
+            ));
+        }
+        Ok(self)
+    }
+}
+
+fn default_strings() -> Vec<StringLiteral> {
+    vec![StringLiteral::new("\"")]
+}
+
+impl LanguageSpec for LanguageDefinition {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn aliases(&self) -> Vec<String> {
+        self.aliases.clone()
+    }
+
+    fn extensions(&self) -> Vec<String> {
+        self.extensions.clone()
+    }
+
+    fn env_var(&self) -> Option<String> {
+        self.env_var.clone()
+    }
+
+    fn default_globs(&self) -> Vec<String> {
+        self.sources
+            .iter()
+            .filter_map(|source| match source.strip_prefix('~') {
+                Some(rest) => env::var("HOME").ok().map(|home| format!("{home}{rest}")),
+                None => Some(source.clone()),
+            })
+            .collect()
+    }
+
+    fn manifests(&self) -> Vec<String> {
+        self.manifests.clone()
+    }
+
+    fn cache_key(&self) -> String {
+        format!("{self:?}")
+    }
+
+    fn syntax(&self) -> Syntax {
+        Syntax {
+            line_comments: self.line_comments.clone(),
+            block_comments: self.block_comments.clone(),
+            nested_comments: self.nested_comments,
+            strings: self.strings.clone(),
+            string_prefixes: self.string_prefixes.clone(),
+            char_literals: self.char_literals,
+            regex_literals: self.regex_literals,
+            raw_strings: self.raw_strings,
+        }
+    }
+
+    fn starts_item(&self, code: &str) -> bool {
+        let code = code.trim_start();
+        self.item_prefixes
+            .iter()
+            .any(|p| code.starts_with(p.as_str()))
+    }
+
+    fn line_filter(&self) -> LineFilter {
+        let mut lexer = Lexer::owned(self.syntax());
+        let filter = self.filter.clone();
+        Box::new(move |line| {
+            let trimmed = line.trim();
+            lexer.line(line) == LineKind::Code
+                && line.len() >= filter.min_length
+                && !filter
+                    .exclude_prefixes
+                    .iter()
+                    .any(|p| trimmed.starts_with(p.as_str()))
+                && !filter
+                    .exclude_containing
+                    .iter()
+                    .any(|c| line.contains(c.as_str()))
+        })
+    }
+}
+
+impl Language {
+    ///
+    /// Registers the language described in the TOML file
+    ///
+    pub fn load_definition<P: AsRef<Path>>(path: P) -> LinesResult<Language> {
+        Ok(Language::register(LanguageDefinition::from_file(path)?))
+    }
+
+    ///
+    /// Registers the languages described in the `.toml` files of the folder
+    ///
+    pub fn load_definitions<P: AsRef<Path>>(folder: P) -> LinesResult<Vec<Language>> {
+        let folder = folder.as_ref();
+        let entries =
+            fs::read_dir(folder).map_err(|e| LinesError::io(&folder.display().to_string(), e))?;
+        let mut paths: Vec<_> = entries
+            .filter_map(Result::ok)
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == "toml"))
+            .collect();
+        paths.sort();
+        paths.iter().map(Language::load_definition).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ELIXIR: &str = r##"
+        name = "Elixir"
+        aliases = ["ex"]
+        extensions = ["ex", "exs"]
+        line_comments = ["#"]
+        block_comments = [['@doc """', '"""']]
+
+        [filter]
+        exclude_prefixes = ["alias ", "import "]
+    "##;
+
+    fn get_lines() -> Vec<String> {
+        vec![
+            "  alias Some.Long.Module".to_string(),
+            "  # a long comment line".to_string(),
+            "  @doc \"\"\"".to_string(),
+            "  Documentation of the function".to_string(),
+            "  \"\"\"".to_string(),
+            "  def thing(a), do: a * 2".to_string(),
+            "  end".to_string(),
+        ]
+    }
+
+    #[test]
+    fn test_definition_filter_lines() {
+        let elixir = LanguageDefinition::from_toml(ELIXIR).unwrap();
+        assert_eq!(
+            elixir.filter_lines(get_lines()),
+            vec!["def thing(a), do: a * 2".to_string(), "end".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_definition_registered() {
+        let elixir = Language::register(LanguageDefinition::from_toml(ELIXIR).unwrap());
+        assert_eq!(elixir, Language::from("ex").unwrap());
+    }
+
+    #[test]
+    fn test_definition_empty_markers() {
+        let definitions = [
+            "line_comments = ['']",
+            "block_comments = [['', '*/']]",
+            "block_comments = [['/*', '']]",
+            "[[strings]]\ndelimiter = ''",
+        ];
+        for definition in definitions {
+            let toml = format!("name = \"A\"\nextensions = []\n{definition}");
+            assert!(matches!(
+                LanguageDefinition::from_toml(&toml),
+                Err(LinesError::InvalidDefinition { path: None, .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_definition_unknown_field() {
+        assert!(
+            LanguageDefinition::from_toml("name = \"A\"\nextensions = []\ncolour = 1").is_err()
+        );
+    }
+}
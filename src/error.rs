@@ -0,0 +1,199 @@
+use std::error::Error;
+use std::{fmt, io};
+
+///
+/// Result of the functions of the library
+///
+pub type LinesResult<T> = Result<T, LinesError>;
+
+///
+/// Error thrown by the library when something goes wrong
+///
+/// The errors of other crates it comes from are available with
+/// [`Error::source`].
+///
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum LinesError {
+    ///
+    /// No registered language has the name or alias
+    ///
+    UnsupportedLanguage { name: String },
+    ///
+    /// `HOME` is not set, so the default folders of the language are unknown
+    ///
+    MissingHome { language: String },
+    ///
+    /// A glob of the files is not valid
+    ///
+    InvalidGlob {
+        pattern: String,
+        source: glob::PatternError,
+    },
+    ///
+    /// A regex of the [`LineConfig`](crate::LineConfig) is not valid
+    ///
+    InvalidRegex {
+        pattern: String,
+        source: regex::Error,
+    },
+    ///
+    /// A [`LanguageDefinition`](crate::LanguageDefinition) is not valid, with
+    /// the file it is in if it was read from one
+    ///
+    InvalidDefinition {
+        path: Option<String>,
+        source: toml::de::Error,
+    },
+    ///
+    /// A file or folder can't be read, the path is none for other readers
+    ///
+    Io {
+        path: Option<String>,
+        source: io::Error,
+    },
+    ///
+    /// No file of the language was found
+    ///
+    NoFiles { language: String },
+    ///
+    /// The file has no line that can be returned, or none of the files has if
+    /// the path is none
+    ///
+    NoLines { path: Option<String> },
+    ///
+    /// There are fewer distinct lines than requested
+    ///
+    NotEnoughLines { requested: usize, found: usize },
+    ///
+    /// The minimum and maximum number of lines of a snippet are not valid
+    ///
+    InvalidSnippetLength { min_lines: usize, max_lines: usize },
+    ///
+    /// None of the files has a snippet with the requested number of lines
+    ///
+    NoSnippet { min_lines: usize, max_lines: usize },
+    ///
+    /// None of the files has an item with at most the requested number of lines
+    ///
+    NoItem { max_lines: usize },
+    ///
+    /// The [`LineConfig`](crate::LineConfig) has no cache to watch
+    ///
+    NoCache,
+    ///
+    /// The files can't be watched, the path is the folder if it is about one
+    ///
+    #[cfg(feature = "watch")]
+    Watch {
+        path: Option<String>,
+        source: notify::Error,
+    },
+}
+
+impl LinesError {
+    ///
+    /// I/O error reading the file at the path
+    ///
+    pub(crate) fn io(path: &str, source: io::Error) -> Self {
+        LinesError::Io {
+            path: Some(path.to_string()),
+            source,
+        }
+    }
+}
+
+impl fmt::Display for LinesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LinesError::UnsupportedLanguage { name } => write!(f, "Language {name} not supported"),
+            LinesError::MissingHome { language } => {
+                write!(f, "HOME is not set to find the files of {language}")
+            }
+            LinesError::InvalidGlob { pattern, source } => {
+                write!(f, "Invalid glob pattern {pattern}: {source}")
+            }
+            LinesError::InvalidRegex { pattern, source } => {
+                write!(f, "Invalid regex {pattern}: {source}")
+            }
+            LinesError::InvalidDefinition { path, source } => match path {
+                Some(path) => write!(f, "Invalid language definition in {path}: {source}"),
+                None => write!(f, "Invalid language definition: {source}"),
+            },
+            LinesError::Io { path, source } => match path {
+                Some(path) => write!(f, "Error reading {path}: {source}"),
+                None => write!(f, "Error reading: {source}"),
+            },
+            LinesError::NoFiles { language } => write!(f, "No files found for {language}"),
+            LinesError::NoLines { path } => match path {
+                Some(path) => write!(f, "No lines found in {path}"),
+                None => write!(f, "No lines found in the files"),
+            },
+            LinesError::NotEnoughLines { requested, found } => {
+                write!(
+                    f,
+                    "Only {found} distinct lines found of the {requested} requested"
+                )
+            }
+            LinesError::InvalidSnippetLength {
+                min_lines,
+                max_lines,
+            } => write!(
+                f,
+                "Invalid snippet length of {min_lines} to {max_lines} lines"
+            ),
+            LinesError::NoSnippet {
+                min_lines,
+                max_lines,
+            } => write!(
+                f,
+                "No snippet of {min_lines} to {max_lines} lines in the files"
+            ),
+            LinesError::NoItem { max_lines } => {
+                write!(f, "No item of at most {max_lines} lines in the files")
+            }
+            LinesError::NoCache => write!(f, "The configuration has no cache to watch"),
+            #[cfg(feature = "watch")]
+            LinesError::Watch { path, source } => match path {
+                Some(path) => write!(f, "Error watching {path}: {source}"),
+                None => write!(f, "Error watching the files: {source}"),
+            },
+        }
+    }
+}
+
+impl Error for LinesError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LinesError::InvalidGlob { source, .. } => Some(source),
+            LinesError::InvalidRegex { source, .. } => Some(source),
+            LinesError::InvalidDefinition { source, .. } => Some(source),
+            LinesError::Io { source, .. } => Some(source),
+            #[cfg(feature = "watch")]
+            LinesError::Watch { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines_error_display() {
+        let error = LinesError::UnsupportedLanguage {
+            name: String::from("brainfuck"),
+        };
+        assert_eq!(error.to_string(), "Language brainfuck not supported");
+        assert!(error.source().is_none());
+    }
+
+    #[test]
+    fn test_lines_error_source() {
+        let error = LinesError::io("thing.rs", io::Error::from(io::ErrorKind::NotFound));
+        assert!(error.to_string().starts_with("Error reading thing.rs: "));
+        let source = error.source().unwrap().downcast_ref::<io::Error>();
+        assert_eq!(source.unwrap().kind(), io::ErrorKind::NotFound);
+    }
+}
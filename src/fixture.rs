@@ -0,0 +1,54 @@
+use std::fs;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::{env, process};
+
+///
+/// A temporary folder for the files of a test, removed with all its files
+/// when it is dropped, even if the test panics
+///
+pub(crate) struct TempFolder(PathBuf);
+
+impl TempFolder {
+    ///
+    /// Creates the folder, the name tells it apart from the ones of the tests
+    /// running at the same time
+    ///
+    pub(crate) fn new(name: &str) -> Self {
+        let path = env::temp_dir().join(format!("code-lines-{name}-{}", process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        TempFolder(path)
+    }
+
+    ///
+    /// Writes the file in the folder, creating the folders it is in, and
+    /// returns its path
+    ///
+    pub(crate) fn write(&self, file: &str, contents: impl AsRef<[u8]>) -> String {
+        let path = self.0.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, contents).unwrap();
+        path.display().to_string()
+    }
+}
+
+impl Deref for TempFolder {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempFolder {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempFolder {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
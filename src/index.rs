@@ -0,0 +1,337 @@
+use rand::seq::SliceRandom;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{BufRead, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use std::{env, io};
+
+use crate::archive;
+use crate::scan::scan;
+use crate::{CodeLine, LineConfig, LinesError, LinesResult};
+
+///
+/// The first line of the index files, with the version of the crate as the
+/// lines are filtered differently from one version to another
+///
+const HEADER: &str = concat!("code-lines ", env!("CARGO_PKG_VERSION"), " index");
+
+///
+/// The files of a configuration with the offsets of their eligible lines,
+/// saved on disk so the files don't have to be searched and read every time
+///
+pub(crate) struct Index {
+    file: PathBuf,
+    entries: BTreeMap<String, Entry>,
+}
+
+///
+/// A file of the index, with the number and byte offset of its eligible lines
+///
+struct Entry {
+    modified: u128,
+    size: u64,
+    lines: Vec<(usize, u64)>,
+}
+
+impl Index {
+    ///
+    /// Loads the index of the configuration from the folder, building it if it
+    /// is not there or it can't be read
+    ///
+    pub(crate) fn open(config: &LineConfig, folder: &Path) -> LinesResult<Self> {
+        let file = folder.join(format!(
+            "{}-{:016x}.index",
+            config.language().name().to_lowercase(),
+            fnv(&config.cache_key())
+        ));
+        if let Some(entries) = fs::read_to_string(&file).ok().and_then(|s| parse(&s)) {
+            return Ok(Index { file, entries });
+        }
+        let paths = config.globbed_paths()?;
+        let scanned = scan(config, &paths, |path| Entry::scan(path, config).ok());
+        let entries = paths
+            .into_iter()
+            .zip(scanned)
+            .filter_map(|(path, entry)| Some((path, entry?)))
+            .collect();
+        let index = Index { file, entries };
+        index.save();
+        Ok(index)
+    }
+
+    ///
+    /// The paths of the files, sorted
+    ///
+    pub(crate) fn paths(&self) -> Vec<String> {
+        self.entries.keys().cloned().collect()
+    }
+
+    ///
+    /// The number of eligible lines of each file, as of the last scan
+    ///
+    pub(crate) fn counts(&self, paths: &[String]) -> Vec<usize> {
+        paths
+            .iter()
+            .map(|p| self.entries.get(p).map_or(0, |e| e.lines.len()))
+            .collect()
+    }
+
+    ///
+    /// Reads a random eligible line of the file, scanning it again first if it
+    /// changed since it was indexed
+    ///
+    pub(crate) fn random_line(&mut self, config: &LineConfig, path: &str) -> LinesResult<CodeLine> {
+        let current = self.entries.get(path).is_some_and(|e| e.is_current(path));
+        if !current {
+            match Entry::scan(path, config) {
+                Ok(entry) => self.entries.insert(path.to_string(), entry),
+                Err(e) => {
+                    self.entries.remove(path);
+                    self.save();
+                    return Err(LinesError::io(path, e));
+                }
+            };
+            self.save();
+        }
+        let lines = &self.entries[path].lines;
+        let Some(&(index, offset)) = lines.choose(&mut *config.rng()) else {
+            return Err(LinesError::NoLines {
+                path: Some(path.to_string()),
+            });
+        };
+        let mut reader = archive::open(path).map_err(|e| LinesError::io(path, e))?;
+        let mut line = String::new();
+        reader
+            .seek(SeekFrom::Start(offset))
+            .and_then(|_| reader.read_line(&mut line))
+            .map_err(|e| LinesError::io(path, e))?;
+        Ok(CodeLine::new(
+            line.trim_end_matches(['\n', '\r']),
+            path,
+            index,
+            config,
+        ))
+    }
+
+    ///
+    /// Scans the files again, removing the ones that are not there anymore
+    /// with the files inside them if they were folders or archives
+    ///
+    #[cfg(feature = "watch")]
+    pub(crate) fn refresh(&mut self, config: &LineConfig, paths: &[String]) {
+        for path in paths {
+            match Entry::scan(path, config) {
+                Ok(entry) => {
+                    self.entries.insert(path.clone(), entry);
+                }
+                Err(_) => {
+                    let folder = format!("{path}/");
+                    let archive = format!("{path}{}", archive::SEPARATOR);
+                    self.entries.retain(|p, _| {
+                        p != path && !p.starts_with(&folder) && !p.starts_with(&archive)
+                    });
+                }
+            }
+        }
+        self.save();
+    }
+
+    ///
+    /// Writes the index to its file, it is built again next time if it fails
+    ///
+    fn save(&self) {
+        let mut contents = format!("{HEADER}\n");
+        for (path, entry) in &self.entries {
+            let lines: Vec<String> = entry
+                .lines
+                .iter()
+                .map(|(i, o)| format!("{i}:{o}"))
+                .collect();
+            contents.push_str(&format!(
+                "{}\t{}\t{}\t{path}\n",
+                entry.modified,
+                entry.size,
+                lines.join(",")
+            ));
+        }
+        let temporary = self.file.with_extension("tmp");
+        let _ = self
+            .file
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&temporary, contents))
+            .and_then(|_| fs::rename(&temporary, &self.file));
+    }
+}
+
+impl Entry {
+    fn scan(path: &str, config: &LineConfig) -> io::Result<Self> {
+        let (modified, size) = stamp(path)?;
+        let mut reader = archive::open(path)?;
+        let mut lines = Vec::new();
+        let mut offsets = Vec::new();
+        let mut offset = 0;
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            let read = reader.read_until(b'\n', &mut line)?;
+            if read == 0 {
+                break;
+            }
+            offsets.push(offset);
+            offset += read as u64;
+            // The lines that are not valid UTF-8 are kept empty so the others
+            // keep their number
+            let text = std::str::from_utf8(&line).unwrap_or_default();
+            lines.push(text.trim_end_matches(['\n', '\r']).to_string());
+        }
+        Ok(Entry {
+            modified,
+            size,
+            lines: config
+                .eligible_lines(&lines)
+                .into_iter()
+                .map(|i| (i, offsets[i]))
+                .collect(),
+        })
+    }
+
+    fn is_current(&self, path: &str) -> bool {
+        stamp(path).is_ok_and(|stamp| stamp == (self.modified, self.size))
+    }
+}
+
+///
+/// The folder of the indexes, `$XDG_CACHE_HOME/code-lines` or
+/// `~/.cache/code-lines`
+///
+pub(crate) fn cache_folder() -> PathBuf {
+    env::var_os("XDG_CACHE_HOME")
+        .filter(|folder| !folder.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+        .unwrap_or_else(env::temp_dir)
+        .join("code-lines")
+}
+
+///
+/// The modification time, in nanoseconds, and the size of the file, or of
+/// the archive it is in
+///
+fn stamp(path: &str) -> io::Result<(u128, u64)> {
+    let metadata = fs::metadata(archive::outer(path))?;
+    let modified = metadata
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos());
+    Ok((modified, metadata.len()))
+}
+
+fn parse(contents: &str) -> Option<BTreeMap<String, Entry>> {
+    let mut rows = contents.lines();
+    if rows.next()? != HEADER {
+        return None;
+    }
+    rows.map(|row| {
+        let mut fields = row.splitn(4, '\t');
+        let modified = fields.next()?.parse().ok()?;
+        let size = fields.next()?.parse().ok()?;
+        let lines = fields
+            .next()?
+            .split(',')
+            .filter(|l| !l.is_empty())
+            .map(|l| {
+                let (index, offset) = l.split_once(':')?;
+                Some((index.parse().ok()?, offset.parse().ok()?))
+            })
+            .collect::<Option<_>>()?;
+        let path = fields.next()?.to_string();
+        Some((
+            path,
+            Entry {
+                modified,
+                size,
+                lines,
+            },
+        ))
+    })
+    .collect()
+}
+
+///
+/// FNV-1a hash, stable between runs and versions to name the index files
+///
+fn fnv(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::TempFolder;
+    use crate::{GoFiles, Language};
+
+    #[test]
+    fn test_index() {
+        let folder = TempFolder::new("index");
+        let source = folder.join("main.rs");
+        fs::write(&source, "// comment\r\nlet value = 1 + 1;\n").unwrap();
+        let path = source.display().to_string();
+        let config = LineConfig::builder()
+            .source(&path)
+            .cache_folder(&folder.display().to_string())
+            .build()
+            .unwrap();
+
+        let mut index = Index::open(&config, &folder).unwrap();
+        assert_eq!(index.paths(), vec![path.clone()]);
+        assert_eq!(index.entries[&path].lines, vec![(1, 12)]);
+        let line = index.random_line(&config, &path).unwrap();
+        assert_eq!(
+            (line.text.as_str(), line.line_number),
+            ("let value = 1 + 1;", 2)
+        );
+
+        let index = Index::open(&config, &folder).unwrap();
+        assert_eq!(index.counts(std::slice::from_ref(&path)), vec![1]);
+
+        fs::write(&source, "let first = 1 + 1;\nlet second = 2 + 2;\n").unwrap();
+        let mut index = Index::open(&config, &folder).unwrap();
+        let line = index.random_line(&config, &path).unwrap();
+        assert!(line.text.starts_with("let"));
+        assert_eq!(index.counts(std::slice::from_ref(&path)), vec![2]);
+
+        let code = b"let first = 1 + 1;\nlet caf\xe9 = 2 + 2;\nlet third = 3 + 3;\n";
+        fs::write(&source, code).unwrap();
+        let line = index.random_line(&config, &path).unwrap();
+        assert!(line.line_number == 1 || line.line_number == 3);
+        assert_eq!(index.entries[&path].lines, vec![(0, 0), (2, 37)]);
+    }
+
+    #[test]
+    fn test_index_spec_variants() {
+        let folder = TempFolder::new("variants");
+        fs::write(folder.join("thing.go"), "package thing\n").unwrap();
+        fs::write(folder.join("thing_test.go"), "var value = 1 + 1\n").unwrap();
+        let config = |language| {
+            LineConfig::builder()
+                .language(language)
+                .source(&format!("{}/*.go", folder.display()))
+                .cache_folder(&folder.join("cache").display().to_string())
+                .build()
+                .unwrap()
+        };
+
+        let go = config(Language::Go);
+        assert!(crate::get_random_line(&go).is_err());
+        let tests = config(Language::go(GoFiles {
+            tests: true,
+            ..GoFiles::default()
+        }));
+        assert_eq!(crate::get_random_line(&tests).unwrap(), "var value = 1 + 1");
+        assert_ne!(go.cache_key(), tests.cache_key());
+    }
+}
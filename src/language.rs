@@ -0,0 +1,545 @@
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+use std::{env, fmt};
+
+use crate::archive;
+use crate::languages::{
+    CFiles, CSpec, GoFiles, GoSpec, JavaSpec, PythonSpec, RustSources, RustSpec, ScriptSpec,
+    BUILT_IN,
+};
+use crate::lexer::{Lexer, LineKind, Syntax};
+use crate::root::{path_list_globs, Search, SourceRoot};
+use crate::{LinesError, LinesResult};
+
+///
+/// Tells if each line is worth returning, given the lines of a file in order
+///
+pub type LineFilter = Box<dyn FnMut(&str) -> bool + Send>;
+
+///
+/// Describes a language the lines can be fetched from
+///
+/// Implement it and call [`Language::register`] to make a new language
+/// available to [`Language::from`] and [`crate::get_random_line`].
+///
+pub trait LanguageSpec: Send + Sync {
+    ///
+    /// The name of the language, used to look it up and to display it
+    ///
+    fn name(&self) -> &str;
+
+    ///
+    /// Other names the language can be looked up with
+    ///
+    fn aliases(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    ///
+    /// Extensions of the source files, without the dot
+    ///
+    fn extensions(&self) -> Vec<String>;
+
+    ///
+    /// Environment variable with the folder to take the source files from
+    ///
+    fn env_var(&self) -> Option<String> {
+        None
+    }
+
+    ///
+    /// Globs of the files used when the environment variable is not set
+    ///
+    fn default_globs(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    ///
+    /// Whether the file found by the globs should be used
+    ///
+    fn accepts_path(&self, _path: &str) -> bool {
+        true
+    }
+
+    ///
+    /// What tells the spec apart from the other ones with the same name, like
+    /// the kinds of files it takes, so they don't share the index of
+    /// [`LineConfigBuilder::cache_folder`](crate::LineConfigBuilder::cache_folder)
+    ///
+    /// Empty by default.
+    ///
+    fn cache_key(&self) -> String {
+        String::new()
+    }
+
+    ///
+    /// Names of the files at the root of a package, like `Cargo.toml`
+    ///
+    fn manifests(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    ///
+    /// The package the file belongs to, used by
+    /// [`Sampling::Packages`](crate::Sampling::Packages)
+    ///
+    /// By default the nearest folder with one of the [`manifests`], or the
+    /// folder of the file if there is none.
+    ///
+    /// [`manifests`]: LanguageSpec::manifests
+    ///
+    fn package(&self, path: &str) -> String {
+        let manifests = self.manifests();
+        let path = Path::new(path);
+        path.ancestors()
+            .skip(1)
+            .find(|folder| manifests.iter().any(|m| folder.join(m).is_file()))
+            .or_else(|| path.parent())
+            .map_or_else(String::new, |folder| folder.display().to_string())
+    }
+
+    ///
+    /// The comments and strings of the language
+    ///
+    fn syntax(&self) -> Syntax;
+
+    ///
+    /// Whether the code of the line, without comments and strings, starts an
+    /// item with its body between braces, like a function or a class
+    ///
+    /// No line does by default.
+    ///
+    fn starts_item(&self, _code: &str) -> bool {
+        false
+    }
+
+    ///
+    /// Whether the line shows that no line of its file is worth returning,
+    /// like the long lines of minified files
+    ///
+    /// It is checked while the lines are read, so the files don't have to be
+    /// read whole. No line does by default.
+    ///
+    fn skips_file(&self, _line: &str) -> bool {
+        false
+    }
+
+    ///
+    /// Tells which lines are worth returning, one line at a time, so the files
+    /// don't have to be read whole
+    ///
+    /// By default the lines with code, according to the [`Syntax`]. The
+    /// [`LineConfig`](crate::LineConfig) filters them further, by length for
+    /// instance.
+    ///
+    fn line_filter(&self) -> LineFilter {
+        let mut lexer = Lexer::owned(self.syntax());
+        Box::new(move |line| lexer.line(line) == LineKind::Code)
+    }
+
+    ///
+    /// Indexes of the lines worth returning
+    ///
+    /// By default the ones the [`line_filter`] accepts, or none if a line
+    /// [`skips_file`]. The lines read one at a time, like with
+    /// [`get_random_line_from`](crate::get_random_line_from), only use those
+    /// two.
+    ///
+    /// [`line_filter`]: LanguageSpec::line_filter
+    /// [`skips_file`]: LanguageSpec::skips_file
+    ///
+    fn eligible_lines(&self, lines: &[String]) -> Vec<usize> {
+        if lines.iter().any(|line| self.skips_file(line)) {
+            return Vec::new();
+        }
+        filtered_lines(self.line_filter(), lines)
+    }
+
+    ///
+    /// Keeps the lines worth returning and trims them
+    ///
+    fn filter_lines(&self, lines: Vec<String>) -> Vec<String> {
+        self.eligible_lines(&lines)
+            .into_iter()
+            .map(|i| lines[i].trim().to_string())
+            .collect()
+    }
+}
+
+///
+/// Indexes of the lines the filter accepts
+///
+pub(crate) fn filtered_lines(mut filter: LineFilter, lines: &[String]) -> Vec<usize> {
+    (0..lines.len()).filter(|&i| filter(&lines[i])).collect()
+}
+
+///
+/// A registered language
+///
+/// It is a cheap handle to a [`LanguageSpec`], two languages are equal when
+/// their names are.
+///
+#[derive(Clone, Copy)]
+pub struct Language(&'static dyn LanguageSpec);
+
+#[allow(non_upper_case_globals)]
+impl Language {
+    pub const Rust: Language = Language(&RustSpec {
+        sources: RustSources::Registry,
+    });
+    pub const Java: Language = Language(&JavaSpec);
+    pub const Python: Language = Language(&PythonSpec);
+    pub const JavaScript: Language = Language(&ScriptSpec { typescript: false });
+    pub const TypeScript: Language = Language(&ScriptSpec { typescript: true });
+    pub const C: Language = Language(&CSpec {
+        cpp: false,
+        files: CFiles::All,
+    });
+    pub const Cpp: Language = Language(&CSpec {
+        cpp: true,
+        files: CFiles::All,
+    });
+    pub const Go: Language = Language(&GoSpec {
+        files: GoFiles {
+            tests: false,
+            generated: false,
+        },
+    });
+}
+
+impl Language {
+    ///
+    /// Looks up a registered language by its name or one of its aliases,
+    /// ignoring case
+    ///
+    pub fn from(lang: &str) -> LinesResult<Self> {
+        let lang_lower = lang.to_lowercase();
+        registry()
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .rev()
+            .find(|l| l.names().contains(&lang_lower))
+            .copied()
+            .ok_or_else(|| LinesError::UnsupportedLanguage {
+                name: lang.to_string(),
+            })
+    }
+
+    ///
+    /// Registers a new language, it replaces any registered language with the
+    /// same name
+    ///
+    /// Registered languages live for the rest of the program.
+    ///
+    pub fn register<S: LanguageSpec + 'static>(spec: S) -> Language {
+        let language = Language(Box::leak(Box::new(spec)));
+        let mut languages = registry().write().unwrap_or_else(|e| e.into_inner());
+        languages.retain(|l| l != &language);
+        languages.push(language);
+        language
+    }
+
+    ///
+    /// All the registered languages
+    ///
+    pub fn registered() -> Vec<Language> {
+        registry().read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    ///
+    /// Rust taking the lines from the selected files, like the standard
+    /// library of the toolchain
+    ///
+    /// It is equal to [`Language::Rust`], as it has the same name. Only
+    /// [`RustSources::Registry`] uses the `RUST_LINES` environment variable.
+    ///
+    pub fn rust(sources: RustSources) -> Language {
+        match sources {
+            RustSources::Registry => Language::Rust,
+            RustSources::Library => Language(&RustSpec {
+                sources: RustSources::Library,
+            }),
+            RustSources::Core => Language(&RustSpec {
+                sources: RustSources::Core,
+            }),
+            RustSources::Alloc => Language(&RustSpec {
+                sources: RustSources::Alloc,
+            }),
+            RustSources::Std => Language(&RustSpec {
+                sources: RustSources::Std,
+            }),
+        }
+    }
+
+    ///
+    /// C taking the lines only from the selected kind of files
+    ///
+    /// It is equal to [`Language::C`], as it has the same name.
+    ///
+    pub fn c(files: CFiles) -> Language {
+        match files {
+            CFiles::All => Language::C,
+            CFiles::Headers => Language(&CSpec {
+                cpp: false,
+                files: CFiles::Headers,
+            }),
+            CFiles::Sources => Language(&CSpec {
+                cpp: false,
+                files: CFiles::Sources,
+            }),
+        }
+    }
+
+    ///
+    /// C++ taking the lines only from the selected kind of files
+    ///
+    /// It is equal to [`Language::Cpp`], as it has the same name.
+    ///
+    pub fn cpp(files: CFiles) -> Language {
+        match files {
+            CFiles::All => Language::Cpp,
+            CFiles::Headers => Language(&CSpec {
+                cpp: true,
+                files: CFiles::Headers,
+            }),
+            CFiles::Sources => Language(&CSpec {
+                cpp: true,
+                files: CFiles::Sources,
+            }),
+        }
+    }
+
+    ///
+    /// Go including the kinds of files that are skipped by default
+    ///
+    /// It is equal to [`Language::Go`], as it has the same name.
+    ///
+    pub fn go(files: GoFiles) -> Language {
+        match (files.tests, files.generated) {
+            (false, false) => Language::Go,
+            (true, false) => Language(&GoSpec {
+                files: GoFiles {
+                    tests: true,
+                    generated: false,
+                },
+            }),
+            (false, true) => Language(&GoSpec {
+                files: GoFiles {
+                    tests: false,
+                    generated: true,
+                },
+            }),
+            (true, true) => Language(&GoSpec {
+                files: GoFiles {
+                    tests: true,
+                    generated: true,
+                },
+            }),
+        }
+    }
+
+    ///
+    /// The name of the language
+    ///
+    pub fn name(&self) -> &'static str {
+        self.0.name()
+    }
+
+    ///
+    /// The specification behind the language
+    ///
+    pub fn spec(&self) -> &'static dyn LanguageSpec {
+        self.0
+    }
+
+    fn names(&self) -> Vec<String> {
+        let mut names = vec![self.0.name().to_lowercase()];
+        names.extend(self.0.aliases().iter().map(|a| a.to_lowercase()));
+        names
+    }
+
+    ///
+    /// The globs of the folders in the environment variable of the language,
+    /// separated by `:`, or else the default globs
+    ///
+    pub(crate) fn globs(&self) -> Vec<String> {
+        match self.0.env_var().and_then(env::var_os) {
+            Some(folders) => path_list_globs(&folders, *self),
+            None => self.0.default_globs(),
+        }
+    }
+
+    ///
+    /// The paths of the files of the language in the environment variable or
+    /// the default folders
+    ///
+    pub(crate) fn get_paths(&self, search: &Search) -> LinesResult<Vec<String>> {
+        let globs = self.globs();
+        if globs.is_empty() && env::var_os("HOME").is_none() {
+            return Err(LinesError::MissingHome {
+                language: self.to_string(),
+            });
+        }
+        self.get_paths_in(&globs, &[], search)
+    }
+
+    ///
+    /// The paths of the files of the language matching the globs or in the
+    /// roots, merged
+    ///
+    pub(crate) fn get_paths_in(
+        &self,
+        globs: &[String],
+        roots: &[SourceRoot],
+        search: &Search,
+    ) -> LinesResult<Vec<String>> {
+        let mut paths = Vec::new();
+        for pattern in globs {
+            paths.extend(
+                self.find(pattern, search)?
+                    .into_iter()
+                    .map(|p| p.display().to_string()),
+            );
+        }
+        for root in roots {
+            for pattern in root.globs(*self) {
+                paths.extend(
+                    self.find(&pattern, search)?
+                        .into_iter()
+                        .filter(|p| !root.excludes(p))
+                        .map(|p| p.display().to_string()),
+                );
+            }
+        }
+        if paths.is_empty() {
+            return Err(LinesError::NoFiles {
+                language: self.to_string(),
+            });
+        }
+        paths.sort();
+        paths.dedup();
+        Ok(paths)
+    }
+
+    ///
+    /// The files of the language matching the glob, with the ones inside the
+    /// archives it matches
+    ///
+    fn find(&self, pattern: &str, search: &Search) -> LinesResult<Vec<PathBuf>> {
+        let mut found = Vec::new();
+        for path in search.find(pattern)? {
+            if archive::is_archive(&path) {
+                found.extend(archive::entries(&path, &self.0.extensions()).unwrap_or_default());
+            } else {
+                found.push(path);
+            }
+        }
+        found.retain(|p| self.0.accepts_path(&p.display().to_string()));
+        Ok(found)
+    }
+}
+
+impl PartialEq for Language {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.name().eq_ignore_ascii_case(other.0.name())
+    }
+}
+
+impl Eq for Language {}
+
+impl fmt::Debug for Language {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Language").field(&self.0.name()).finish()
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0.name())
+    }
+}
+
+fn registry() -> &'static RwLock<Vec<Language>> {
+    static REGISTRY: OnceLock<RwLock<Vec<Language>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(BUILT_IN.to_vec()))
+}
+
+///
+/// Returns the path with the home folder prepended, if `HOME` is set
+///
+pub(crate) fn home_glob(path: &str) -> Option<String> {
+    env::var("HOME").ok().map(|home| format!("{home}/{path}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::TempFolder;
+
+    struct Kotlin;
+
+    impl LanguageSpec for Kotlin {
+        fn name(&self) -> &str {
+            "Kotlin"
+        }
+
+        fn aliases(&self) -> Vec<String> {
+            vec![String::from("kt")]
+        }
+
+        fn extensions(&self) -> Vec<String> {
+            vec![String::from("kt")]
+        }
+
+        fn syntax(&self) -> Syntax {
+            Syntax::default()
+        }
+    }
+
+    #[test]
+    fn test_language_register() {
+        let kotlin = Language::register(Kotlin);
+        assert_eq!(kotlin, Language::from("KOTLIN").unwrap());
+        assert_eq!(kotlin, Language::from("kt").unwrap());
+        assert!(Language::registered().contains(&kotlin));
+    }
+
+    #[test]
+    fn test_language_package() {
+        let root = TempFolder::new("crate");
+        let folder = root.join("src").join("parser");
+        std::fs::create_dir_all(&folder).unwrap();
+        std::fs::write(root.join("Cargo.toml"), "").unwrap();
+
+        let path = folder.join("mod.rs").display().to_string();
+        assert_eq!(
+            Language::Rust.spec().package(&path),
+            root.display().to_string()
+        );
+        assert_eq!(
+            Language::C.spec().package(&path),
+            folder.display().to_string()
+        );
+    }
+
+    #[test]
+    fn test_language_from_unknown() {
+        assert!(matches!(
+            Language::from("brainfuck"),
+            Err(LinesError::UnsupportedLanguage { name }) if name == "brainfuck"
+        ));
+    }
+
+    #[test]
+    fn test_language_c_files() {
+        assert_eq!(Language::C, Language::c(CFiles::Headers));
+        assert_eq!(Language::Cpp, Language::from("c++").unwrap());
+        assert_ne!(Language::C, Language::cpp(CFiles::Sources));
+    }
+
+    #[test]
+    fn test_language_display() {
+        assert_eq!("Java", Language::Java.to_string());
+    }
+}
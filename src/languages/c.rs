@@ -0,0 +1,198 @@
+use super::{c_like_syntax, declares_function, without_modifiers};
+use crate::lexer::{Lexer, LineKind, RawStrings, StringLiteral, Syntax};
+use crate::{LanguageSpec, LineFilter};
+
+const C_HEADERS: &[&str] = &["h"];
+const C_SOURCES: &[&str] = &["c"];
+const CPP_HEADERS: &[&str] = &["hpp", "hh", "hxx"];
+const CPP_SOURCES: &[&str] = &["cpp", "cc", "cxx"];
+
+///
+/// The headers of the C++ standard library, most of them without extension
+/// like `vector`
+///
+const CPP_LIBRARY_HEADERS: &str = "/usr/include/c++/**/*";
+
+///
+/// The kind of C or C++ files the lines are taken from
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CFiles {
+    ///
+    /// Both headers and sources
+    ///
+    All,
+    ///
+    /// Only headers, like `.h` or `.hpp`
+    ///
+    Headers,
+    ///
+    /// Only sources, like `.c` or `.cpp`
+    ///
+    Sources,
+}
+
+pub(crate) struct CSpec {
+    pub(crate) cpp: bool,
+    pub(crate) files: CFiles,
+}
+
+impl CSpec {
+    fn headers(&self) -> &'static [&'static str] {
+        match (self.cpp, self.files) {
+            (_, CFiles::Sources) => &[],
+            (false, _) => C_HEADERS,
+            (true, _) => CPP_HEADERS,
+        }
+    }
+
+    fn sources(&self) -> &'static [&'static str] {
+        match (self.cpp, self.files) {
+            (_, CFiles::Headers) => &[],
+            (false, _) => C_SOURCES,
+            (true, _) => CPP_SOURCES,
+        }
+    }
+}
+
+impl LanguageSpec for CSpec {
+    fn name(&self) -> &str {
+        if self.cpp {
+            "C++"
+        } else {
+            "C"
+        }
+    }
+
+    fn aliases(&self) -> Vec<String> {
+        if self.cpp {
+            vec![String::from("cpp"), String::from("cxx")]
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn extensions(&self) -> Vec<String> {
+        self.headers()
+            .iter()
+            .chain(self.sources())
+            .map(|ext| ext.to_string())
+            .collect()
+    }
+
+    fn env_var(&self) -> Option<String> {
+        Some(String::from(if self.cpp { "CPP_LINES" } else { "C_LINES" }))
+    }
+
+    fn default_globs(&self) -> Vec<String> {
+        let headers = self
+            .headers()
+            .iter()
+            .map(|ext| format!("/usr/include/**/*.{ext}"));
+        let library =
+            (self.cpp && self.files != CFiles::Sources).then(|| String::from(CPP_LIBRARY_HEADERS));
+        let sources = self
+            .sources()
+            .iter()
+            .map(|ext| format!("/usr/src/**/*.{ext}"));
+        headers.chain(library).chain(sources).collect()
+    }
+
+    fn cache_key(&self) -> String {
+        format!("{:?}", self.files)
+    }
+
+    fn syntax(&self) -> Syntax {
+        let syntax = c_like_syntax(vec![StringLiteral::new("\""), StringLiteral::new("'")]);
+        if self.cpp {
+            Syntax {
+                raw_strings: RawStrings::Delimited,
+                ..syntax
+            }
+        } else {
+            syntax
+        }
+    }
+
+    fn starts_item(&self, code: &str) -> bool {
+        let code = without_modifiers(code, &["static", "inline", "extern", "virtual", "template"]);
+        ["struct ", "class ", "union ", "namespace "]
+            .iter()
+            .any(|item| code.starts_with(item) && !code.trim_end().ends_with(';'))
+            || (!code.starts_with('#') && declares_function(code))
+    }
+
+    ///
+    /// Skips the preprocessor directives, with their continuation lines, which
+    /// include the include guards
+    ///
+    fn line_filter(&self) -> LineFilter {
+        let mut lexer = Lexer::owned(self.syntax());
+        let mut in_directive = false;
+        Box::new(move |line| {
+            let kind = lexer.line(line);
+            let trimmed = line.trim();
+            if in_directive || trimmed.starts_with('#') {
+                in_directive = trimmed.ends_with('\\');
+                return false;
+            }
+            kind == LineKind::Code
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_lines() -> Vec<String> {
+        vec![
+            "#ifndef THING_H".to_string(),
+            "#define THING_H".to_string(),
+            "#include <stdio.h>".to_string(),
+            "#define HALF(x) \\".to_string(),
+            "    ((x) / 2)".to_string(),
+            "int half(int value) {".to_string(),
+            "    return value / 2;".to_string(),
+            "}".to_string(),
+            "#endif /* THING_H */".to_string(),
+        ]
+    }
+
+    #[test]
+    fn test_c_filter_lines() {
+        let c = CSpec {
+            cpp: false,
+            files: CFiles::All,
+        };
+        assert_eq!(
+            c.filter_lines(get_lines()),
+            vec![
+                "int half(int value) {".to_string(),
+                "return value / 2;".to_string(),
+                "}".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cpp_extensions() {
+        let headers = CSpec {
+            cpp: true,
+            files: CFiles::Headers,
+        };
+        assert_eq!(headers.extensions(), vec!["hpp", "hh", "hxx"]);
+        assert!(headers.default_globs()[0].starts_with("/usr/include/"));
+        assert!(headers
+            .default_globs()
+            .contains(&String::from("/usr/include/c++/**/*")));
+        let sources = CSpec {
+            cpp: true,
+            files: CFiles::Sources,
+        };
+        assert!(sources
+            .default_globs()
+            .iter()
+            .all(|glob| glob.starts_with("/usr/src/")));
+    }
+}
@@ -0,0 +1,177 @@
+use std::env;
+
+use super::c_like_syntax;
+use crate::language::home_glob;
+use crate::lexer::{Lexer, LineKind, StringLiteral, Syntax};
+use crate::{LanguageSpec, LineFilter};
+
+///
+/// The kinds of Go files that are skipped unless included
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GoFiles {
+    ///
+    /// Include the `_test.go` files
+    ///
+    pub tests: bool,
+    ///
+    /// Include the files with the `// Code generated ... DO NOT EDIT.` header
+    ///
+    pub generated: bool,
+}
+
+pub(crate) struct GoSpec {
+    pub(crate) files: GoFiles,
+}
+
+impl LanguageSpec for GoSpec {
+    fn name(&self) -> &str {
+        "Go"
+    }
+
+    fn aliases(&self) -> Vec<String> {
+        vec![String::from("golang")]
+    }
+
+    fn extensions(&self) -> Vec<String> {
+        vec![String::from("go")]
+    }
+
+    fn env_var(&self) -> Option<String> {
+        Some(String::from("GO_LINES"))
+    }
+
+    fn default_globs(&self) -> Vec<String> {
+        let gopath = env::var("GOPATH")
+            .ok()
+            .and_then(|paths| paths.split(':').next().map(String::from))
+            .filter(|path| !path.is_empty());
+        match gopath {
+            Some(path) => vec![format!("{path}/pkg/mod/**/*.go")],
+            None => home_glob("go/pkg/mod/**/*.go").into_iter().collect(),
+        }
+    }
+
+    fn accepts_path(&self, path: &str) -> bool {
+        self.files.tests || !path.ends_with("_test.go")
+    }
+
+    fn cache_key(&self) -> String {
+        format!("{:?}", self.files)
+    }
+
+    fn manifests(&self) -> Vec<String> {
+        vec![String::from("go.mod")]
+    }
+
+    fn syntax(&self) -> Syntax {
+        c_like_syntax(vec![
+            StringLiteral::new("\""),
+            StringLiteral::new("'"),
+            StringLiteral::new("`").multiline().unescaped(),
+        ])
+    }
+
+    fn starts_item(&self, code: &str) -> bool {
+        let code = code.trim();
+        code.starts_with("func ") || (code.starts_with("type ") && code.ends_with('{'))
+    }
+
+    ///
+    /// Skips the package and imports, and the lines after the header of a
+    /// generated file
+    ///
+    fn line_filter(&self) -> LineFilter {
+        let mut lexer = Lexer::owned(self.syntax());
+        let skip_generated = !self.files.generated;
+        let mut generated = false;
+        let mut in_imports = false;
+        Box::new(move |line| {
+            let kind = lexer.line(line);
+            let trimmed = line.trim();
+            generated |= skip_generated && is_generated_header(line);
+            if in_imports {
+                in_imports = trimmed != ")";
+                return false;
+            }
+            in_imports = trimmed == "import (";
+            !generated
+                && !in_imports
+                && kind == LineKind::Code
+                && !trimmed.starts_with("package ")
+                && !trimmed.starts_with("import ")
+        })
+    }
+
+    fn skips_file(&self, line: &str) -> bool {
+        !self.files.generated && is_generated_header(line)
+    }
+}
+
+///
+/// Whether the line is the comment marking the file as generated
+///
+fn is_generated_header(line: &str) -> bool {
+    line.starts_with("// Code generated ") && line.trim_end().ends_with(" DO NOT EDIT.")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_lines() -> Vec<String> {
+        vec![
+            "package thing".to_string(),
+            "import (".to_string(),
+            "    \"github.com/some/module\"".to_string(),
+            ")".to_string(),
+            "func Half(value int) int {".to_string(),
+            "    return value / 2".to_string(),
+            "}".to_string(),
+        ]
+    }
+
+    #[test]
+    fn test_go_filter_lines() {
+        let go = GoSpec {
+            files: GoFiles::default(),
+        };
+        assert_eq!(
+            go.filter_lines(get_lines()),
+            vec![
+                "func Half(value int) int {".to_string(),
+                "return value / 2".to_string(),
+                "}".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_go_filter_lines_generated() {
+        let mut lines = get_lines();
+        lines.insert(
+            0,
+            "// Code generated by protoc-gen-go. DO NOT EDIT.".to_string(),
+        );
+        let go = GoSpec {
+            files: GoFiles::default(),
+        };
+        assert!(go.filter_lines(lines.clone()).is_empty());
+        let go = GoSpec {
+            files: GoFiles {
+                generated: true,
+                ..GoFiles::default()
+            },
+        };
+        assert_eq!(go.filter_lines(lines).len(), 3);
+    }
+
+    #[test]
+    fn test_go_accepts_path() {
+        let go = GoSpec {
+            files: GoFiles::default(),
+        };
+        assert!(go.accepts_path("mod/thing/thing.go"));
+        assert!(!go.accepts_path("mod/thing/thing_test.go"));
+    }
+}
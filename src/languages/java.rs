@@ -0,0 +1,78 @@
+use std::env;
+
+use super::{c_like_syntax, declares_function, without_modifiers};
+use crate::language::home_glob;
+use crate::lexer::{Lexer, LineKind, StringLiteral, Syntax};
+use crate::{LanguageSpec, LineFilter};
+
+pub(crate) struct JavaSpec;
+
+impl LanguageSpec for JavaSpec {
+    fn name(&self) -> &str {
+        "Java"
+    }
+
+    fn extensions(&self) -> Vec<String> {
+        vec![String::from("java")]
+    }
+
+    fn env_var(&self) -> Option<String> {
+        Some(String::from("JAVA_LINES"))
+    }
+
+    fn default_globs(&self) -> Vec<String> {
+        env::var("JAVA_HOME")
+            .ok()
+            .filter(|home| !home.is_empty())
+            .map(|home| format!("{home}/lib/src.zip"))
+            .into_iter()
+            .chain(home_glob(".m2/repository/**/*-sources.jar"))
+            .collect()
+    }
+
+    fn manifests(&self) -> Vec<String> {
+        vec![
+            String::from("pom.xml"),
+            String::from("build.gradle"),
+            String::from("build.gradle.kts"),
+        ]
+    }
+
+    fn syntax(&self) -> Syntax {
+        c_like_syntax(vec![
+            StringLiteral::new("\"\"\"").multiline(),
+            StringLiteral::new("\""),
+            StringLiteral::new("'"),
+        ])
+    }
+
+    fn starts_item(&self, code: &str) -> bool {
+        let code = without_modifiers(
+            code,
+            &[
+                "public",
+                "protected",
+                "private",
+                "static",
+                "final",
+                "abstract",
+                "sealed",
+                "strictfp",
+                "default",
+                "native",
+                "synchronized",
+            ],
+        );
+        ["class ", "interface ", "enum ", "record "]
+            .iter()
+            .any(|item| code.starts_with(item))
+            || declares_function(code)
+    }
+
+    fn line_filter(&self) -> LineFilter {
+        let mut lexer = Lexer::owned(self.syntax());
+        Box::new(move |line| {
+            lexer.line(line) == LineKind::Code && !line.trim_start().starts_with("import ")
+        })
+    }
+}
@@ -0,0 +1,165 @@
+use std::env;
+use std::sync::OnceLock;
+
+use super::{c_like_syntax, command_output, without_modifiers};
+use crate::lexer::{Lexer, LineKind, StringLiteral, Syntax};
+use crate::{LanguageSpec, LineFilter};
+
+///
+/// Lines longer than this are only found in minified files
+///
+const MINIFIED_LINE_LENGTH: usize = 500;
+
+pub(crate) struct ScriptSpec {
+    pub(crate) typescript: bool,
+}
+
+impl LanguageSpec for ScriptSpec {
+    fn name(&self) -> &str {
+        if self.typescript {
+            "TypeScript"
+        } else {
+            "JavaScript"
+        }
+    }
+
+    fn aliases(&self) -> Vec<String> {
+        vec![String::from(if self.typescript { "ts" } else { "js" })]
+    }
+
+    fn extensions(&self) -> Vec<String> {
+        let extensions = if self.typescript {
+            ["ts", "mts", "cts", "tsx"]
+        } else {
+            ["js", "mjs", "cjs", "jsx"]
+        };
+        extensions.map(String::from).to_vec()
+    }
+
+    fn env_var(&self) -> Option<String> {
+        Some(String::from(if self.typescript {
+            "TS_LINES"
+        } else {
+            "JS_LINES"
+        }))
+    }
+
+    fn default_globs(&self) -> Vec<String> {
+        node_modules_globs(&self.extensions())
+    }
+
+    fn accepts_path(&self, path: &str) -> bool {
+        !is_bundle(path)
+    }
+
+    fn manifests(&self) -> Vec<String> {
+        vec![String::from("package.json")]
+    }
+
+    fn syntax(&self) -> Syntax {
+        Syntax {
+            regex_literals: true,
+            ..c_like_syntax(vec![
+                StringLiteral::new("\""),
+                StringLiteral::new("'"),
+                StringLiteral::new("`").multiline(),
+            ])
+        }
+    }
+
+    fn starts_item(&self, code: &str) -> bool {
+        let code = without_modifiers(code, &["export", "default", "declare", "abstract", "async"]);
+        ["function ", "function* ", "class ", "interface "]
+            .iter()
+            .any(|item| code.starts_with(item))
+    }
+
+    ///
+    /// Skips the imports, and the long lines of minified files
+    ///
+    fn line_filter(&self) -> LineFilter {
+        let mut lexer = Lexer::owned(self.syntax());
+        Box::new(move |line| {
+            lexer.line(line) == LineKind::Code
+                && line.len() <= MINIFIED_LINE_LENGTH
+                && !line.trim_start().starts_with("import ")
+        })
+    }
+
+    fn skips_file(&self, line: &str) -> bool {
+        line.len() > MINIFIED_LINE_LENGTH
+    }
+}
+
+///
+/// Globs of the files with the extensions in the `node_modules` folders of the
+/// project in `NODE_PROJECT` (or the current folder) and the global npm prefix
+///
+fn node_modules_globs(extensions: &[String]) -> Vec<String> {
+    let project = env::var("NODE_PROJECT").unwrap_or_else(|_| String::from("."));
+    let mut folders = vec![format!("{project}/node_modules")];
+    if let Some(prefix) = npm_global_prefix() {
+        folders.push(format!("{prefix}/lib/node_modules"));
+    }
+    folders
+        .iter()
+        .flat_map(|folder| {
+            extensions
+                .iter()
+                .map(move |ext| format!("{folder}/**/*.{ext}"))
+        })
+        .collect()
+}
+
+fn npm_global_prefix() -> Option<String> {
+    if let Ok(prefix) = env::var("NPM_CONFIG_PREFIX") {
+        return Some(prefix);
+    }
+    static OUTPUT: OnceLock<Option<String>> = OnceLock::new();
+    command_output(&OUTPUT, &["npm"], &["prefix", "-g"]).map(String::from)
+}
+
+///
+/// Whether the path is of a minified or built file
+///
+fn is_bundle(path: &str) -> bool {
+    let file = path.rsplit('/').next().unwrap_or(path);
+    file.contains(".min.")
+        || file.contains(".bundle.")
+        || path.split('/').any(|folder| folder == "dist")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_bundle() {
+        assert!(is_bundle("node_modules/react/umd/react.min.js"));
+        assert!(is_bundle("node_modules/lodash/dist/lodash.js"));
+        assert!(!is_bundle("node_modules/lodash/chunk.js"));
+    }
+
+    #[test]
+    fn test_javascript_filter_lines() {
+        let lines = vec![
+            "import { thing } from './thing';".to_string(),
+            "/**".to_string(),
+            " * Returns the half of the value".to_string(),
+            " */".to_string(),
+            "const half = (value) => value / 2;".to_string(),
+        ];
+        assert_eq!(
+            ScriptSpec { typescript: false }.filter_lines(lines),
+            vec!["const half = (value) => value / 2;".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_javascript_filter_lines_minified() {
+        let lines = vec!["var a = 1;".repeat(100), "const half = 1 / 2;".to_string()];
+        assert!(ScriptSpec { typescript: true }
+            .filter_lines(lines)
+            .is_empty());
+    }
+}
@@ -0,0 +1,119 @@
+mod c;
+mod go;
+mod java;
+mod javascript;
+mod python;
+mod rust;
+
+pub use c::CFiles;
+pub(crate) use c::CSpec;
+pub use go::GoFiles;
+pub(crate) use go::GoSpec;
+pub(crate) use java::JavaSpec;
+pub(crate) use javascript::ScriptSpec;
+pub(crate) use python::PythonSpec;
+pub use rust::RustSources;
+pub(crate) use rust::RustSpec;
+
+use std::process::Command;
+use std::sync::OnceLock;
+
+use crate::lexer::{StringLiteral, Syntax};
+use crate::Language;
+
+///
+/// The languages registered from the start
+///
+pub(crate) const BUILT_IN: &[Language] = &[
+    Language::Rust,
+    Language::Java,
+    Language::Python,
+    Language::JavaScript,
+    Language::TypeScript,
+    Language::C,
+    Language::Cpp,
+    Language::Go,
+];
+
+///
+/// Removes the leading modifier words from the code
+///
+pub(crate) fn without_modifiers<'a>(code: &'a str, modifiers: &[&str]) -> &'a str {
+    let mut code = code.trim();
+    while let Some(modifier) = modifiers.iter().find(|m| {
+        code.strip_prefix(**m)
+            .is_some_and(|rest| rest.starts_with(char::is_whitespace))
+    }) {
+        code = code[modifier.len()..].trim_start();
+    }
+    code
+}
+
+///
+/// Whether the code looks like the declaration of a function in a language
+/// like Java or C, a type and a name followed by the parameters
+///
+pub(crate) fn declares_function(code: &str) -> bool {
+    const NOT_TYPES: &[&str] = &[
+        "if",
+        "else",
+        "for",
+        "while",
+        "do",
+        "switch",
+        "case",
+        "catch",
+        "return",
+        "new",
+        "throw",
+        "sizeof",
+        "synchronized",
+        "try",
+    ];
+    let Some((declaration, _)) = code.split_once('(') else {
+        return false;
+    };
+    let declaration = declaration.trim_start().trim_start_matches('}');
+    let words: Vec<&str> = declaration.split_whitespace().collect();
+    words.len() >= 2
+        && !NOT_TYPES.contains(&words[0])
+        && !code.contains('=')
+        && !code.trim_end().ends_with(';')
+        && words[words.len() - 1]
+            .trim_start_matches(['*', '&'])
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == ':' || c == '~')
+}
+
+///
+/// The trimmed output of the first of the programs that runs fine with the
+/// arguments
+///
+/// The output is kept in the cell, so the programs are only run once.
+///
+pub(crate) fn command_output(
+    cell: &'static OnceLock<Option<String>>,
+    programs: &[&str],
+    args: &[&str],
+) -> Option<&'static str> {
+    cell.get_or_init(|| {
+        programs
+            .iter()
+            .filter_map(|program| Command::new(program).args(args).output().ok())
+            .find(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    })
+    .as_deref()
+}
+
+///
+/// Syntax with `//` and `/* */` comments and the given strings
+///
+pub(crate) fn c_like_syntax(strings: Vec<StringLiteral>) -> Syntax {
+    Syntax {
+        line_comments: vec![String::from("//")],
+        block_comments: vec![(String::from("/*"), String::from("*/"))],
+        strings,
+        ..Syntax::default()
+    }
+}
@@ -0,0 +1,158 @@
+use std::path::Path;
+use std::sync::OnceLock;
+
+use super::command_output;
+use crate::lexer::{Lexer, LineKind, StringLiteral, Syntax};
+use crate::{LanguageSpec, LineFilter};
+
+const SITE_PACKAGES: &str = "import site; print('\\n'.join(site.getsitepackages()))";
+
+pub(crate) struct PythonSpec;
+
+impl LanguageSpec for PythonSpec {
+    fn name(&self) -> &str {
+        "Python"
+    }
+
+    fn aliases(&self) -> Vec<String> {
+        vec![String::from("py")]
+    }
+
+    fn extensions(&self) -> Vec<String> {
+        vec![String::from("py")]
+    }
+
+    fn env_var(&self) -> Option<String> {
+        Some(String::from("PYTHON_LINES"))
+    }
+
+    fn default_globs(&self) -> Vec<String> {
+        site_packages()
+            .iter()
+            .map(|folder| format!("{folder}/**/*.py"))
+            .collect()
+    }
+
+    fn package(&self, path: &str) -> String {
+        let path = Path::new(path);
+        path.ancestors()
+            .skip(1)
+            .take_while(|folder| folder.join("__init__.py").is_file())
+            .last()
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    }
+
+    fn syntax(&self) -> Syntax {
+        Syntax {
+            line_comments: vec![String::from("#")],
+            strings: vec![
+                StringLiteral::new("\"\"\"").multiline(),
+                StringLiteral::new("'''").multiline(),
+                StringLiteral::new("\""),
+                StringLiteral::new("'"),
+            ],
+            string_prefixes: vec!['r', 'R', 'b', 'B', 'u', 'U', 'f', 'F'],
+            ..Syntax::default()
+        }
+    }
+
+    fn line_filter(&self) -> LineFilter {
+        let mut lexer = Lexer::owned(self.syntax());
+        let mut decorator_depth = 0;
+        Box::new(move |line| {
+            let kind = lexer.line(line);
+            let trimmed = line.trim();
+            if decorator_depth > 0 {
+                decorator_depth += bracket_balance(trimmed);
+                return false;
+            }
+            if kind != LineKind::Code {
+                return false;
+            }
+            if trimmed.starts_with('@') {
+                decorator_depth = bracket_balance(trimmed).max(0);
+                return false;
+            }
+            !trimmed.starts_with("import ") && !trimmed.starts_with("from ")
+        })
+    }
+}
+
+///
+/// Site-packages folders of the first python interpreter found
+///
+fn site_packages() -> Vec<String> {
+    static OUTPUT: OnceLock<Option<String>> = OnceLock::new();
+    command_output(&OUTPUT, &["python3", "python"], &["-c", SITE_PACKAGES])
+        .map(|output| output.lines().map(String::from).collect())
+        .unwrap_or_default()
+}
+
+///
+/// Opened minus closed brackets in the line
+///
+fn bracket_balance(line: &str) -> i32 {
+    line.chars()
+        .map(|c| match c {
+            '(' | '[' | '{' => 1,
+            ')' | ']' | '}' => -1,
+            _ => 0,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::TempFolder;
+
+    fn get_lines() -> Vec<String> {
+        vec![
+            "import collections.abc".to_string(),
+            "@functools.lru_cache(".to_string(),
+            "    maxsize=None,".to_string(),
+            ")".to_string(),
+            "def thing(value, other):".to_string(),
+            "    \"\"\"".to_string(),
+            "    Returns the thing of the value".to_string(),
+            "    \"\"\"".to_string(),
+            "    r'''Single line docstring'''".to_string(),
+            "    # divide the value by other".to_string(),
+            "    query = '''".to_string(),
+            "    y = value / other".to_string(),
+            "    '''".to_string(),
+            "    return value / other".to_string(),
+        ]
+    }
+
+    #[test]
+    fn test_python_package() {
+        let root = TempFolder::new("py");
+        let module = root.join("requests").join("adapters");
+        std::fs::create_dir_all(&module).unwrap();
+        std::fs::write(root.join("requests").join("__init__.py"), "").unwrap();
+        std::fs::write(module.join("__init__.py"), "").unwrap();
+
+        let path = module.join("http.py").display().to_string();
+        assert_eq!(
+            PythonSpec.package(&path),
+            root.join("requests").display().to_string()
+        );
+        let path = root.join("six.py").display().to_string();
+        assert_eq!(PythonSpec.package(&path), path);
+    }
+
+    #[test]
+    fn test_python_filter_lines() {
+        assert_eq!(
+            PythonSpec.filter_lines(get_lines()),
+            vec![
+                "def thing(value, other):".to_string(),
+                "query = '''".to_string(),
+                "return value / other".to_string()
+            ]
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use super::{c_like_syntax, command_output, without_modifiers};
+use crate::language::home_glob;
+use crate::lexer::{RawStrings, StringLiteral, Syntax};
+use crate::LanguageSpec;
+
+const REGISTRY: &str = ".cargo/registry/src";
+
+///
+/// The Rust files the lines are taken from
+///
+/// The standard library is the `rust-src` component of the active rustup
+/// toolchain, added with `rustup component add rust-src`.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RustSources {
+    ///
+    /// The crates of the cargo registry, or the standard library if the
+    /// registry is empty
+    ///
+    Registry,
+    ///
+    /// The `core`, `alloc` and `std` crates and the rest of the standard
+    /// library
+    ///
+    Library,
+    ///
+    /// Only the `core` crate of the standard library
+    ///
+    Core,
+    ///
+    /// Only the `alloc` crate of the standard library
+    ///
+    Alloc,
+    ///
+    /// Only the `std` crate of the standard library
+    ///
+    Std,
+}
+
+pub(crate) struct RustSpec {
+    pub(crate) sources: RustSources,
+}
+
+impl LanguageSpec for RustSpec {
+    fn name(&self) -> &str {
+        "Rust"
+    }
+
+    fn aliases(&self) -> Vec<String> {
+        vec![String::from("rs")]
+    }
+
+    fn extensions(&self) -> Vec<String> {
+        vec![String::from("rs")]
+    }
+
+    fn env_var(&self) -> Option<String> {
+        (self.sources == RustSources::Registry).then(|| String::from("RUST_LINES"))
+    }
+
+    fn default_globs(&self) -> Vec<String> {
+        if self.sources == RustSources::Registry {
+            let registry = home_glob(REGISTRY);
+            if registry
+                .as_ref()
+                .is_some_and(|r| !is_empty_folder(Path::new(r)))
+            {
+                return registry
+                    .map(|r| format!("{r}/**/*.rs"))
+                    .into_iter()
+                    .collect();
+            }
+        }
+        sysroot()
+            .map(|sysroot| library_globs(&sysroot, self.sources))
+            .unwrap_or_default()
+    }
+
+    fn cache_key(&self) -> String {
+        format!("{:?}", self.sources)
+    }
+
+    fn manifests(&self) -> Vec<String> {
+        vec![String::from("Cargo.toml")]
+    }
+
+    fn syntax(&self) -> Syntax {
+        Syntax {
+            nested_comments: true,
+            char_literals: true,
+            raw_strings: RawStrings::Hashed,
+            string_prefixes: vec!['b', 'c'],
+            ..c_like_syntax(vec![StringLiteral::new("\"").multiline()])
+        }
+    }
+
+    fn starts_item(&self, code: &str) -> bool {
+        let code = without_modifiers(code, &["pub", "async", "const", "unsafe", "default"]);
+        let code = match code.strip_prefix("pub(") {
+            Some(rest) => rest.split_once(')').map_or(rest, |(_, r)| r).trim_start(),
+            None => code,
+        };
+        let code = without_modifiers(code, &["async", "const", "unsafe", "extern", "default"]);
+        ["fn ", "impl ", "impl<", "trait ", "struct ", "enum "]
+            .iter()
+            .any(|item| code.starts_with(item))
+    }
+}
+
+///
+/// The globs of the standard library in the sysroot of a toolchain
+///
+fn library_globs(sysroot: &Path, sources: RustSources) -> Vec<String> {
+    let library = sysroot.join("lib/rustlib/src/rust/library");
+    let krate = match sources {
+        RustSources::Registry | RustSources::Library => "*",
+        RustSources::Core => "core",
+        RustSources::Alloc => "alloc",
+        RustSources::Std => "std",
+    };
+    vec![format!("{}/{krate}/src/**/*.rs", library.display())]
+}
+
+///
+/// The sysroot of the active toolchain, from `rustc --print sysroot`
+///
+fn sysroot() -> Option<PathBuf> {
+    static OUTPUT: OnceLock<Option<String>> = OnceLock::new();
+    command_output(&OUTPUT, &["rustc"], &["--print", "sysroot"]).map(PathBuf::from)
+}
+
+///
+/// Whether the folder has nothing in it or is not there
+///
+fn is_empty_folder(folder: &Path) -> bool {
+    fs::read_dir(folder).map_or(true, |mut entries| entries.next().is_none())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_library_globs() {
+        let sysroot = Path::new("/toolchains/stable");
+        assert_eq!(
+            library_globs(sysroot, RustSources::Core),
+            vec!["/toolchains/stable/lib/rustlib/src/rust/library/core/src/**/*.rs"]
+        );
+        assert_eq!(
+            library_globs(sysroot, RustSources::Library),
+            vec!["/toolchains/stable/lib/rustlib/src/rust/library/*/src/**/*.rs"]
+        );
+    }
+}
@@ -0,0 +1,605 @@
+use serde::Deserialize;
+use std::borrow::Cow;
+
+///
+/// The comments and string literals of a language
+///
+/// It is all the [`Lexer`] needs to tell the lines of code from the comments.
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Syntax {
+    ///
+    /// Markers starting a comment that lasts until the end of the line
+    ///
+    pub line_comments: Vec<String>,
+    ///
+    /// Pairs of markers opening and closing a block comment
+    ///
+    pub block_comments: Vec<(String, String)>,
+    ///
+    /// Whether block comments can be nested, like in Rust
+    ///
+    pub nested_comments: bool,
+    ///
+    /// The string literals, the longest delimiter matching wins
+    ///
+    pub strings: Vec<StringLiteral>,
+    ///
+    /// Letters that can go right before a string and are part of it, like the
+    /// `r` and `b` in the Python `rb"..."`
+    ///
+    pub string_prefixes: Vec<char>,
+    ///
+    /// Whether `'` only starts a literal when it is a char, like `'a'`, and is
+    /// code otherwise, like in the lifetime `'a`
+    ///
+    pub char_literals: bool,
+    ///
+    /// Whether `/` starts a regex literal after an operator, an opening
+    /// bracket or `return`, like in JavaScript
+    ///
+    pub regex_literals: bool,
+    ///
+    /// The style of the raw strings of the language
+    ///
+    pub raw_strings: RawStrings,
+}
+
+///
+/// A string literal, delimited by the same marker at both ends
+///
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StringLiteral {
+    ///
+    /// The marker opening and closing the string
+    ///
+    pub delimiter: String,
+    ///
+    /// The character escaping the next one inside the string
+    ///
+    #[serde(default)]
+    pub escape: Option<char>,
+    ///
+    /// Whether the string can span several lines
+    ///
+    #[serde(default)]
+    pub multiline: bool,
+}
+
+impl StringLiteral {
+    ///
+    /// A single line string escaped with `\`
+    ///
+    pub fn new(delimiter: &str) -> Self {
+        StringLiteral {
+            delimiter: delimiter.to_string(),
+            escape: Some('\\'),
+            multiline: false,
+        }
+    }
+
+    ///
+    /// The same string, but able to span several lines
+    ///
+    pub fn multiline(self) -> Self {
+        StringLiteral {
+            multiline: true,
+            ..self
+        }
+    }
+
+    ///
+    /// The same string, but without escapes
+    ///
+    pub fn unescaped(self) -> Self {
+        StringLiteral {
+            escape: None,
+            ..self
+        }
+    }
+}
+
+///
+/// Raw strings, with delimiters chosen by the writer
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RawStrings {
+    ///
+    /// The language has no raw strings
+    ///
+    #[default]
+    None,
+    ///
+    /// Rust raw strings, like `r#"..."#`
+    ///
+    Hashed,
+    ///
+    /// C++ raw strings, like `R"delimiter(...)delimiter"`
+    ///
+    Delimited,
+}
+
+///
+/// What a line contains, as told by the [`Lexer`]
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineKind {
+    ///
+    /// Only whitespace
+    ///
+    Blank,
+    ///
+    /// Only comments
+    ///
+    Comment,
+    ///
+    /// Only parts of string literals, like the lines of a docstring
+    ///
+    String,
+    ///
+    /// Some code, maybe next to comments or strings
+    ///
+    Code,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum State {
+    Code,
+    BlockComment { comment: usize, depth: usize },
+    String { literal: usize },
+    RawString { close: String },
+}
+
+///
+/// Lightweight lexer telling what each line of a file contains
+///
+/// The lines have to be given in order, as comments and strings can span
+/// several of them.
+///
+pub struct Lexer<'a> {
+    syntax: Cow<'a, Syntax>,
+    state: State,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(syntax: &'a Syntax) -> Self {
+        Lexer {
+            syntax: Cow::Borrowed(syntax),
+            state: State::Code,
+        }
+    }
+
+    ///
+    /// Lexer that keeps the syntax, to store it or move it to a closure
+    ///
+    pub fn owned(syntax: Syntax) -> Lexer<'static> {
+        Lexer {
+            syntax: Cow::Owned(syntax),
+            state: State::Code,
+        }
+    }
+
+    ///
+    /// Reads the next line of the file
+    ///
+    pub fn line(&mut self, line: &str) -> LineKind {
+        self.scan(line, |_| ())
+    }
+
+    ///
+    /// Reads the next line of the file, also returning its code without the
+    /// comments and the strings
+    ///
+    pub fn code(&mut self, line: &str) -> (LineKind, String) {
+        let mut code = String::new();
+        let kind = self.scan(line, |c| code.push(c));
+        (kind, code)
+    }
+
+    fn scan<F: FnMut(char)>(&mut self, line: &str, mut on_code: F) -> LineKind {
+        let mut code = false;
+        let mut comment = matches!(self.state, State::BlockComment { .. });
+        let mut string = matches!(self.state, State::String { .. } | State::RawString { .. });
+        let mut i = 0;
+        while let Some(c) = line[i..].chars().next() {
+            let rest = &line[i..];
+            match &mut self.state {
+                State::BlockComment { comment, depth } => {
+                    let (open, close) = &self.syntax.block_comments[*comment];
+                    if self.syntax.nested_comments && rest.starts_with(open.as_str()) {
+                        *depth += 1;
+                        i += open.len();
+                    } else if rest.starts_with(close.as_str()) {
+                        *depth -= 1;
+                        i += close.len();
+                        if *depth == 0 {
+                            self.state = State::Code;
+                        }
+                    } else {
+                        i += c.len_utf8();
+                    }
+                }
+                State::String { literal } => {
+                    let literal = &self.syntax.strings[*literal];
+                    if Some(c) == literal.escape {
+                        i += c.len_utf8();
+                        i += line[i..].chars().next().map_or(0, char::len_utf8);
+                    } else if rest.starts_with(literal.delimiter.as_str()) {
+                        i += literal.delimiter.len();
+                        self.state = State::Code;
+                    } else {
+                        i += c.len_utf8();
+                    }
+                }
+                State::RawString { close } => {
+                    if rest.starts_with(close.as_str()) {
+                        i += close.len();
+                        self.state = State::Code;
+                    } else {
+                        i += c.len_utf8();
+                    }
+                }
+                State::Code => {
+                    if c.is_whitespace() {
+                        on_code(c);
+                        i += c.len_utf8();
+                    } else if self.starts_line_comment(rest) {
+                        comment = true;
+                        break;
+                    } else if let Some((index, (open, _))) = self.starts_block_comment(rest) {
+                        comment = true;
+                        i += open.len();
+                        self.state = State::BlockComment {
+                            comment: index,
+                            depth: 1,
+                        };
+                    } else if let Some((open, close)) = self.starts_raw_string(line, i) {
+                        string = true;
+                        i += open;
+                        self.state = State::RawString { close };
+                    } else if self.syntax.char_literals && c == '\'' {
+                        match char_literal_len(rest) {
+                            Some(len) => {
+                                string = true;
+                                i += len;
+                            }
+                            None => {
+                                code = true;
+                                on_code(c);
+                                i += 1;
+                            }
+                        }
+                    } else if let Some(len) = self.regex_literal_len(line, i) {
+                        string = true;
+                        i += len;
+                    } else if let Some((prefix, index)) = self.starts_string(line, i) {
+                        string = true;
+                        i += prefix + self.syntax.strings[index].delimiter.len();
+                        self.state = State::String { literal: index };
+                    } else {
+                        code = true;
+                        on_code(c);
+                        i += c.len_utf8();
+                    }
+                }
+            }
+        }
+        if let State::String { literal } = self.state {
+            let literal = &self.syntax.strings[literal];
+            let continued = literal.escape.is_some_and(|e| line.ends_with(e));
+            if !literal.multiline && !continued {
+                self.state = State::Code;
+            }
+        }
+        if code {
+            LineKind::Code
+        } else if string {
+            LineKind::String
+        } else if comment {
+            LineKind::Comment
+        } else {
+            LineKind::Blank
+        }
+    }
+
+    fn starts_line_comment(&self, rest: &str) -> bool {
+        self.syntax
+            .line_comments
+            .iter()
+            .any(|marker| rest.starts_with(marker.as_str()))
+    }
+
+    fn starts_block_comment(&self, rest: &str) -> Option<(usize, &(String, String))> {
+        self.syntax
+            .block_comments
+            .iter()
+            .enumerate()
+            .find(|(_, (open, _))| rest.starts_with(open.as_str()))
+    }
+
+    ///
+    /// The length of the prefix and the index of the string starting at `i`
+    ///
+    fn starts_string(&self, line: &str, i: usize) -> Option<(usize, usize)> {
+        let rest = &line[i..];
+        let prefix = if follows_identifier(line, i) {
+            0
+        } else {
+            rest.len()
+                - rest
+                    .trim_start_matches(self.syntax.string_prefixes.as_slice())
+                    .len()
+        };
+        let rest = &rest[prefix..];
+        self.syntax
+            .strings
+            .iter()
+            .enumerate()
+            .filter(|(_, literal)| rest.starts_with(literal.delimiter.as_str()))
+            .max_by_key(|(_, literal)| literal.delimiter.len())
+            .map(|(index, _)| (prefix, index))
+    }
+
+    ///
+    /// The length of the regex literal starting at `i`, with its flags, if it
+    /// is one and not a division
+    ///
+    fn regex_literal_len(&self, line: &str, i: usize) -> Option<usize> {
+        let rest = &line[i..];
+        if !self.syntax.regex_literals
+            || !rest.starts_with('/')
+            || rest[1..].starts_with(['/', '*'])
+        {
+            return None;
+        }
+        let before = line[..i].trim_end();
+        let after_operator = before.ends_with(REGEX_PRECEDERS)
+            || before
+                .strip_suffix("return")
+                .is_some_and(|b| !b.ends_with(|c: char| c.is_alphanumeric() || c == '_'));
+        if !after_operator {
+            return None;
+        }
+        let mut class = false;
+        let mut chars = rest.char_indices().skip(1);
+        while let Some((end, c)) = chars.next() {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                '[' => class = true,
+                ']' => class = false,
+                '/' if !class => {
+                    let flags = rest[end + 1..]
+                        .chars()
+                        .take_while(char::is_ascii_alphabetic)
+                        .count();
+                    return Some(end + 1 + flags);
+                }
+                _ => (),
+            }
+        }
+        None
+    }
+
+    ///
+    /// The length of the opening of the raw string starting at `i`, and the
+    /// marker closing it
+    ///
+    fn starts_raw_string(&self, line: &str, i: usize) -> Option<(usize, String)> {
+        if follows_identifier(line, i) {
+            return None;
+        }
+        let rest = &line[i..];
+        match self.syntax.raw_strings {
+            RawStrings::None => None,
+            RawStrings::Hashed => {
+                let after_r = rest
+                    .strip_prefix("br")
+                    .or_else(|| rest.strip_prefix("cr"))
+                    .or_else(|| rest.strip_prefix('r'))?;
+                let hashes = after_r.len() - after_r.trim_start_matches('#').len();
+                after_r[hashes..].starts_with('"').then(|| {
+                    let open = rest.len() - after_r.len() + hashes + 1;
+                    (open, format!("\"{}", "#".repeat(hashes)))
+                })
+            }
+            RawStrings::Delimited => {
+                let after_r = rest.strip_prefix("R\"")?;
+                let delimiter = &after_r[..after_r.find('(')?];
+                (delimiter.len() <= 16 && !delimiter.contains([' ', ')', '\\', '"']))
+                    .then(|| (delimiter.len() + 3, format!("){delimiter}\"")))
+            }
+        }
+    }
+}
+
+///
+/// The characters after which a `/` starts a regex literal instead of a
+/// division
+///
+const REGEX_PRECEDERS: &[char] = &['(', ',', '=', ':', '[', '!', '&', '|', '?', '{', ';'];
+
+///
+/// Whether the character before `i` is part of an identifier
+///
+fn follows_identifier(line: &str, i: usize) -> bool {
+    line[..i]
+        .chars()
+        .next_back()
+        .is_some_and(|c| c.is_alphanumeric() || c == '_')
+}
+
+///
+/// The length of the char literal at the start of `rest`, if it is one and
+/// not a lifetime or a label
+///
+fn char_literal_len(rest: &str) -> Option<usize> {
+    let mut chars = rest.char_indices().skip(1);
+    match chars.next()? {
+        (_, '\\') => {
+            chars.next()?;
+            chars
+                .take(10)
+                .find(|(_, c)| *c == '\'')
+                .map(|(end, _)| end + 1)
+        }
+        (_, c) => match chars.next() {
+            Some((end, '\'')) if c != '\'' => Some(end + 1),
+            _ => None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rust() -> Syntax {
+        Syntax {
+            line_comments: vec![String::from("//")],
+            block_comments: vec![(String::from("/*"), String::from("*/"))],
+            nested_comments: true,
+            strings: vec![StringLiteral::new("\"").multiline()],
+            char_literals: true,
+            regex_literals: false,
+            raw_strings: RawStrings::Hashed,
+            string_prefixes: vec!['b'],
+        }
+    }
+
+    fn kinds(syntax: &Syntax, lines: &[&str]) -> Vec<LineKind> {
+        let mut lexer = Lexer::new(syntax);
+        lines.iter().map(|l| lexer.line(l)).collect()
+    }
+
+    #[test]
+    fn test_lexer_division_and_paths() {
+        let syntax = rust();
+        assert_eq!(
+            kinds(
+                &syntax,
+                &[
+                    "let half = |a| a / 2;",
+                    "let url = \"https://example.com\"; // a comment",
+                    "    // let commented = out();",
+                    "/// Doc comment",
+                    "",
+                ]
+            ),
+            vec![
+                LineKind::Code,
+                LineKind::Code,
+                LineKind::Comment,
+                LineKind::Comment,
+                LineKind::Blank
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lexer_nested_block_comments() {
+        let syntax = rust();
+        assert_eq!(
+            kinds(
+                &syntax,
+                &[
+                    "/* outer /* inner */",
+                    "   still a comment",
+                    "*/ let a = 1;"
+                ]
+            ),
+            vec![LineKind::Comment, LineKind::Comment, LineKind::Code]
+        );
+    }
+
+    #[test]
+    fn test_lexer_strings_and_chars() {
+        let syntax = rust();
+        assert_eq!(
+            kinds(
+                &syntax,
+                &[
+                    "let raw = r#\"",
+                    "    // not a comment \"",
+                    "\"#;",
+                    "let slash = '/'; fn f<'a>(s: &'a str) {}",
+                    "let c = '\\''; // quote",
+                    "let multi = \"start",
+                    "    end\";",
+                ]
+            ),
+            vec![
+                LineKind::Code,
+                LineKind::String,
+                LineKind::Code,
+                LineKind::Code,
+                LineKind::Code,
+                LineKind::Code,
+                LineKind::Code
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lexer_cpp_raw_strings() {
+        let syntax = Syntax {
+            line_comments: vec![String::from("//")],
+            strings: vec![StringLiteral::new("\"")],
+            raw_strings: RawStrings::Delimited,
+            ..Syntax::default()
+        };
+        assert_eq!(
+            kinds(&syntax, &["auto s = R\"x(", "// text)\"", ")x\";"]),
+            vec![LineKind::Code, LineKind::String, LineKind::Code]
+        );
+    }
+
+    #[test]
+    fn test_lexer_regex_literals() {
+        let syntax = Syntax {
+            line_comments: vec![String::from("//")],
+            block_comments: vec![(String::from("/*"), String::from("*/"))],
+            strings: vec![StringLiteral::new("'")],
+            regex_literals: true,
+            ..Syntax::default()
+        };
+        let mut lexer = Lexer::new(&syntax);
+        assert_eq!(
+            lexer.code("path = path.replace(/\\/*$/, '');"),
+            (LineKind::Code, String::from("path = path.replace(, );"))
+        );
+        assert_eq!(
+            lexer.code("return /[/*]+/g.test(name) && half / 2 / 1;"),
+            (
+                LineKind::Code,
+                String::from("return .test(name) && half / 2 / 1;")
+            )
+        );
+        assert_eq!(
+            kinds(&syntax, &["const pattern = /a*b/i;", "// comment"]),
+            vec![LineKind::Code, LineKind::Comment]
+        );
+    }
+
+    #[test]
+    fn test_lexer_code() {
+        let syntax = rust();
+        let mut lexer = Lexer::new(&syntax);
+        assert_eq!(
+            (LineKind::Code, String::from("let a =  { ; ")),
+            lexer.code("let a = \"}\" { '}'; // }")
+        );
+    }
+
+    #[test]
+    fn test_char_literal_len() {
+        assert_eq!(Some(3), char_literal_len("'a'"));
+        assert_eq!(Some(4), char_literal_len("'\\n'"));
+        assert_eq!(Some(11), char_literal_len("'\\u{1F600}'"));
+        assert_eq!(Some(4), char_literal_len("'\\''"));
+        assert_eq!(None, char_literal_len("'a str"));
+    }
+}
@@ -1,180 +1,184 @@
-use glob::glob;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
-use std::{env, fmt};
-use std::{
-    error::Error,
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use rand::Rng;
+use std::io::BufRead;
 
-type LinesResult<T> = Result<T, LinesError>;
-
-///
-/// The supported languages
-///
-#[derive(PartialEq, Debug)]
-pub enum Language {
-    Rust,
-    Java,
-}
-
-impl Copy for Language {}
-impl Clone for Language {
-   fn clone(&self) -> Self {
-       Language::Rust
-   } 
-}
-
-impl Language {
-    pub fn from(lang: &str)-> LinesResult<Self> {
-        match lang.to_lowercase().as_str() {
-            "rust" => Ok(Language::Rust),
-            "java" => Ok(Language::Java),
-            _ => Err(LinesError(format!("Language {lang} not supported"))),
-        }
-    }
-
-    fn default_folder(&self) -> Option<String> {
-        let home = match env::var("HOME") {
-            Ok(h) => h,
-            Err(_) => return None,
-        };
-
-        match self {
-            Language::Rust => Some(String::from(&format!("{home}/.cargo/registry/src/**/*.rs"))),
-            Language::Java => None,
-        }
-    }
-
-    fn env_var_folder(&self) -> Option<String> {
-        match self {
-            Language::Rust => match env::var("RUST_LINES") {
-                Ok(folder) => return Some(format!("{folder}/**/*.rust")),
-                Err(_) => None,
-            },
-            Language::Java => match env::var("JAVA_LINES") {
-                Ok(folder) => return Some(format!("{folder}/**/*.java")),
-                Err(_) => None,
-            },
-        }
-    }
-
-    fn folder(&self) -> Option<String> {
-        if self.env_var_folder().is_some() {
-            return self.env_var_folder();
-        }
-        if self.default_folder().is_some() {
-            return self.default_folder();
-        }
-        None
-    }
+mod archive;
+mod batch;
+mod config;
+mod definition;
+mod error;
+#[cfg(test)]
+mod fixture;
+mod index;
+mod language;
+mod languages;
+mod lexer;
+mod line;
+mod reservoir;
+mod root;
+mod sampling;
+mod scan;
+mod snippet;
+mod stream;
+#[cfg(feature = "watch")]
+mod watch;
 
-    fn get_paths(&self) -> LinesResult<Vec<String>> {
-        if let Some(folder) = &self.folder() {
-            if let Ok(paths) = glob(folder) {
-                return Ok(paths
-                    .filter_map(Result::ok)
-                    .map(|p| p.display().to_string())
-                    .collect());
-            };
-        }
-        Err(LinesError(format!(
-            "Error getting file paths for {}.",
-            self
-        )))
-    }
-
-    fn filter_lines(&self, lines: Vec<String>) -> Vec<String> {
-        match self {
-            Language::Rust => lines
-                .into_iter()
-                .filter(|l| !l.contains('/') && l.len() > 10)
-                .map(|l| l.trim().to_string())
-                .collect(),
-            Language::Java => lines
-                .into_iter()
-                .filter(|l| !l.contains('/') && l.len() > 10 && !l.contains("import"))
-                .map(|l| l.trim().to_string())
-                .collect(),
-        }
-    }
-}
+use sampling::Candidates;
 
-impl fmt::Display for Language {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Language::Rust => write!(f, "Rust"),
-            Language::Java => write!(f, "Java"),
-        }
-    }
-}
+pub use batch::get_random_lines;
+pub use config::{LineConfig, LineConfigBuilder};
+pub use definition::{FilterRules, LanguageDefinition};
+pub use error::{LinesError, LinesResult};
+pub use language::{Language, LanguageSpec, LineFilter};
+pub use languages::{CFiles, GoFiles, RustSources};
+pub use lexer::{Lexer, LineKind, RawStrings, StringLiteral, Syntax};
+pub use line::CodeLine;
+pub use reservoir::get_random_line_from;
+pub use root::SourceRoot;
+pub use sampling::Sampling;
+pub use snippet::{get_random_item, get_random_snippet};
+pub use stream::LineStream;
+#[cfg(feature = "watch")]
+pub use watch::IndexWatcher;
 
 ///
-/// Configuration of the requested lines
+/// Returns the text of a random line of code that matches de config argument  
+/// It returns a [`LinesError`] if something bad happens
 ///
-pub struct LineConfig {
-    ///
-    /// The language that you want the lines from
-    ///
-    pub language: Language,
-}
-
+/// It is [`get_random_code_line`] without the details of where the line is from.
 ///
-/// Error thrown by the library when something goes wrong
+/// # Arguments
 ///
-#[derive(Debug)]
-pub struct LinesError(String);
-
-impl fmt::Display for LinesError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "There is an error: {}", self.0)
-    }
+/// * `config` - A reference to a [`LineConfig`]
+///
+pub fn get_random_line(config: &LineConfig) -> LinesResult<String> {
+    get_random_code_line(config).map(|line| line.text)
 }
 
-impl Error for LinesError {}
-
 ///
-/// Returns a random line of code that matches de config argument  
+/// Returns a random line of code that matches de config argument, with the
+/// file and the line number it is from  
 /// It returns a [`LinesError`] if something bad happens
 ///
 /// The lines are retrieved from the files in the specified folder and subfolders
 /// in this order:
-/// 1. From environment variable folder if set up (JAVA_LINES, RUST_LINES)
-/// 2. From the default folder (Only for rust)
+/// 1. From the folder in the environment variable of the language, if set
+///    (RUST_LINES, JAVA_LINES...)
+/// 2. From the default folders of the language, if it has any
+///
+/// See the README for the environment variable and default folders of each
+/// built-in language.
+///
+/// The files without lines, or that can't be read, are skipped for as long as
+/// the config lives. After [`retries`](LineConfigBuilder::retries) of them the
+/// rest of the files are tried in turn, and an error is only returned if none
+/// has lines.
 ///
 /// # Arguments
 ///
 /// * `config` - A reference to a [`LineConfig`]
 ///
-pub fn get_random_line(config: &LineConfig) -> LinesResult<String> {
-    match File::open(get_random_file_path(config)?) {
-        Ok(file) => get_random_string(&&config.language.filter_lines(get_lines_from_file(file))),
-        Err(e) => Err(LinesError(e.to_string())),
+pub fn get_random_code_line(config: &LineConfig) -> LinesResult<CodeLine> {
+    let line = in_random_file(config, true, |path| {
+        match get_random_line_in(config, path) {
+            Err(LinesError::NoLines { .. }) => Ok(None),
+            result => result.map(Some),
+        }
+    })?;
+    line.ok_or(LinesError::NoLines { path: None })
+}
+
+///
+/// Calls the function on random files until it finds what it looks for in
+/// one, none if no file has it
+///
+/// The files it finds nothing in, or that can't be read, are not tried again.
+/// With `exhaust` they are remembered as files without lines for as long as
+/// the config lives. After [`retries`](LineConfigBuilder::retries) of them
+/// the rest of the files are tried in turn.
+///
+fn in_random_file<T>(
+    config: &LineConfig,
+    exhaust: bool,
+    mut find: impl FnMut(&str) -> LinesResult<Option<T>>,
+) -> LinesResult<Option<T>> {
+    let mut find_in = |path: &str| match find(path) {
+        Ok(None) | Err(LinesError::Io { path: Some(_), .. }) => {
+            if exhaust {
+                config.exhaust(path);
+            }
+            Ok(None)
+        }
+        result => result,
+    };
+    let mut paths = config.paths()?;
+    for _ in 0..=config.retries() {
+        if exhaust {
+            config.skip_exhausted(&mut paths);
+        }
+        let path = pick_file_path(config, &paths)?;
+        if let Some(found) = find_in(&path)? {
+            return Ok(Some(found));
+        }
+        paths.retain(|p| p != &path);
+    }
+    if exhaust {
+        config.skip_exhausted(&mut paths);
+    }
+    paths.shuffle(&mut *config.rng());
+    for path in paths {
+        if let Some(found) = find_in(&path)? {
+            return Ok(Some(found));
+        }
     }
+    Ok(None)
 }
 
-fn get_lines_from_file(file: File) -> Vec<String> {
-    BufReader::new(file)
-        .lines()
-        .filter_map(Result::ok)
-        .collect()
+fn get_random_line_in(config: &LineConfig, path: &str) -> LinesResult<CodeLine> {
+    if let Some(line) = config.cached(|index| index.random_line(config, path)) {
+        return line;
+    }
+    let file = archive::open(path).map_err(|e| LinesError::io(path, e))?;
+    match get_random_line_from(config, file) {
+        Ok(line) => Ok(CodeLine {
+            path: path.to_string(),
+            ..line
+        }),
+        Err(LinesError::NoLines { .. }) => Err(LinesError::NoLines {
+            path: Some(path.to_string()),
+        }),
+        Err(e) => Err(e),
+    }
 }
 
-fn get_random_file_path(config: &LineConfig) -> LinesResult<String> {
-    get_random_string(&config.language.get_paths()?)
+fn get_lines_from_file(file: impl BufRead) -> Vec<String> {
+    file.lines().map_while(Result::ok).collect()
 }
 
-fn get_random_string(lines: &Vec<String>) -> LinesResult<String> {
-    match lines.choose(&mut thread_rng()) {
+fn pick_file_path(config: &LineConfig, paths: &[String]) -> LinesResult<String> {
+    if config.sampling() == Sampling::Files {
+        return get_random_string(paths, &mut *config.rng());
+    }
+    let candidates = Candidates::new(config, paths.to_vec());
+    match candidates.pick(config) {
+        Some(position) => Ok(candidates.path(position).to_string()),
+        None => Err(LinesError::NoLines { path: None }),
+    }
+}
+
+fn get_random_string<R: Rng + ?Sized>(lines: &[String], rng: &mut R) -> LinesResult<String> {
+    match lines.choose(rng) {
         Some(line) => Ok(line.to_string()),
-        None => Err(LinesError(String::from("Error getting random string."))),
+        None => Err(LinesError::NoLines { path: None }),
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison, clippy::get_first, clippy::useless_vec)]
 mod tests {
     use super::*;
+    use crate::fixture::TempFolder;
+    use rand::thread_rng;
 
     fn get_lines() -> Vec<String> {
         vec![
@@ -187,11 +191,9 @@ mod tests {
 
     #[test]
     fn test_language_filter_lines_java() {
-        let config = LineConfig {
-            language: Language::Java,
-        };
+        let config = LineConfig::new(Language::Java);
 
-        let result = config.language.filter_lines(get_lines());
+        let result = config.filter_lines(get_lines());
         assert_eq!(result.len(), 1);
         assert_eq!(
             result.get(0).unwrap(),
@@ -201,11 +203,9 @@ mod tests {
 
     #[test]
     fn test_language_filter_lines_rust() {
-        let config = LineConfig {
-            language: Language::Rust,
-        };
+        let config = LineConfig::new(Language::Rust);
 
-        let result = config.language.filter_lines(get_lines());
+        let result = config.filter_lines(get_lines());
         assert_eq!(result.len(), 2);
         assert_eq!(
             result.get(1).unwrap(),
@@ -215,23 +215,65 @@ mod tests {
 
     #[test]
     fn test_get_random_string_one_string() {
-        let result = get_random_string(&vec![String::from("random")]);
+        let result = get_random_string(&vec![String::from("random")], &mut thread_rng());
         assert_eq!(result.unwrap(), String::from("random"));
     }
 
     #[test]
     fn test_get_random_string_no_strings() {
-        let result = get_random_string(&vec![]);
+        let result = get_random_string(&vec![], &mut thread_rng());
         assert_eq!(true, result.is_err());
     }
 
     #[test]
     fn test_get_random_string_various_strings() {
         let thing = vec![String::from("o"), String::from("a")];
-        let result = get_random_string(&thing);
+        let result = get_random_string(&thing, &mut thread_rng());
         assert_eq!(true, thing.contains(&result.unwrap()));
     }
 
+    #[test]
+    fn test_get_random_code_line_skips_empty_files() {
+        let folder = TempFolder::new("retry");
+        for i in 0..5 {
+            folder.write(&format!("mod{i}.rs"), "//! Only docs\n");
+        }
+        let glob = format!("{}/*.rs", folder.display());
+        let config = LineConfig::builder().source(&glob).retries(1).build();
+        let config = config.unwrap();
+        assert!(matches!(
+            get_random_code_line(&config),
+            Err(LinesError::NoLines { path: None })
+        ));
+
+        let lib = folder.write("lib.rs", "let value = 1 + 1;\n");
+        for _ in 0..5 {
+            assert_eq!(get_random_line(&config).unwrap(), "let value = 1 + 1;");
+        }
+        let mut paths = config.paths().unwrap();
+        config.skip_exhausted(&mut paths);
+        assert_eq!(paths, vec![lib]);
+    }
+
+    #[test]
+    fn test_get_random_code_line_skips_missing_files() {
+        let folder = TempFolder::new("missing");
+        for name in ["gone", "kept"] {
+            folder.write(&format!("{name}.rs"), format!("let {name} = 1 + 1;\n"));
+        }
+        let config = LineConfig::builder()
+            .source(&format!("{}/*.rs", folder.display()))
+            .cache_folder(&folder.join("cache").display().to_string())
+            .retries(0)
+            .build()
+            .unwrap();
+        assert_eq!(config.paths().unwrap().len(), 2);
+
+        std::fs::remove_file(folder.join("gone.rs")).unwrap();
+        for _ in 0..5 {
+            assert_eq!(get_random_line(&config).unwrap(), "let kept = 1 + 1;");
+        }
+    }
 
     #[test]
     fn test_language_from_java() {
@@ -0,0 +1,75 @@
+use std::fmt;
+
+use crate::{Language, LineConfig};
+
+///
+/// A line of code and where it comes from
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeLine {
+    ///
+    /// The line, without the trailing whitespace, and without the indentation
+    /// unless the [`LineConfig`] keeps it
+    ///
+    pub text: String,
+    ///
+    /// The path of the file the line is from
+    ///
+    pub path: String,
+    ///
+    /// The number of the line in the file, starting at 1
+    ///
+    pub line_number: usize,
+    ///
+    /// The language of the file
+    ///
+    pub language: Language,
+    ///
+    /// The whitespace the line starts with in the file
+    ///
+    pub indentation: String,
+}
+
+impl CodeLine {
+    ///
+    /// Builds the code line from the line at `index`, starting at 0, of the file
+    ///
+    pub(crate) fn new(line: &str, path: &str, index: usize, config: &LineConfig) -> Self {
+        CodeLine {
+            text: config.text(line).to_string(),
+            path: path.to_string(),
+            line_number: index + 1,
+            language: config.language(),
+            indentation: line[..line.len() - line.trim_start().len()].to_string(),
+        }
+    }
+
+    ///
+    /// The line as it is in the file, with its indentation
+    ///
+    pub fn original(&self) -> String {
+        format!("{}{}", self.indentation, self.text.trim_start())
+    }
+}
+
+impl fmt::Display for CodeLine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_line_new() {
+        let config = LineConfig::new(Language::Rust);
+        let line = CodeLine::new("\t    let half = value / 2;  ", "src/lib.rs", 4, &config);
+        assert_eq!("let half = value / 2;", line.text);
+        assert_eq!("\t    ", line.indentation);
+        assert_eq!(5, line.line_number);
+        assert_eq!("\t    let half = value / 2;", line.original());
+        assert_eq!("src/lib.rs", line.path);
+    }
+}
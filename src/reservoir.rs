@@ -0,0 +1,113 @@
+use rand::Rng;
+use std::io::BufRead;
+
+use crate::{CodeLine, LineConfig, LinesError, LinesResult};
+
+///
+/// Returns a random line of code from the reader that matches the config
+/// argument, reading it once and keeping only the chosen line in memory  
+/// It returns a [`LinesError`] if the reader fails or it has no such line
+///
+/// The lines are filtered as they are read, with the
+/// [`line_filter`](crate::LanguageSpec::line_filter) and the
+/// [`skips_file`](crate::LanguageSpec::skips_file) of the language. The lines
+/// that are not valid UTF-8 are skipped. The path of the line is empty.
+///
+/// # Arguments
+///
+/// * `config` - A reference to a [`LineConfig`]
+/// * `reader` - The reader of the code, like a `BufReader` of a file
+///
+pub fn get_random_line_from<R: BufRead>(
+    config: &LineConfig,
+    mut reader: R,
+) -> LinesResult<CodeLine> {
+    let spec = config.language().spec();
+    let mut filter = config.line_filter();
+    let mut chosen = None;
+    let mut seen = 0;
+    let mut line = Vec::new();
+    for index in 0.. {
+        line.clear();
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(source) => return Err(LinesError::Io { path: None, source }),
+        }
+        let Ok(text) = std::str::from_utf8(&line) else {
+            continue;
+        };
+        let text = text.trim_end_matches(['\n', '\r']);
+        if spec.skips_file(text) {
+            return Err(LinesError::NoLines { path: None });
+        }
+        if !filter(text) {
+            continue;
+        }
+        seen += 1;
+        if config.rng().gen_range(0..seen) == 0 {
+            chosen = Some((index, text.to_string()));
+        }
+    }
+    match chosen {
+        Some((index, text)) => Ok(CodeLine::new(&text, "", index, config)),
+        None => Err(LinesError::NoLines { path: None }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Language;
+
+    const CODE: &str = "import java.util.List;\n\
+        /* let first = 1 + 1;\n\
+        let second = 2 + 2; */\r\n\
+        let third = 3 + 3;\r\n\
+        let fourth = 4 + 4;\n";
+
+    #[test]
+    fn test_get_random_line_from() {
+        let config = LineConfig::new(Language::Java);
+        let mut found: Vec<(usize, String)> = (0..50)
+            .map(|_| get_random_line_from(&config, CODE.as_bytes()).unwrap())
+            .map(|line| (line.line_number, line.text))
+            .collect();
+        found.sort();
+        found.dedup();
+        assert_eq!(
+            found,
+            vec![
+                (4, "let third = 3 + 3;".to_string()),
+                (5, "let fourth = 4 + 4;".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_random_line_from_invalid_utf8() {
+        let config = LineConfig::new(Language::Java);
+        let code = b"let first = 1 + 1;\nlet caf\xe9 = 2 + 2;\nlet third = 3 + 3;\n";
+        let lines: Vec<usize> = (0..50)
+            .map(|_| get_random_line_from(&config, &code[..]).unwrap())
+            .map(|line| line.line_number)
+            .collect();
+        assert!(lines.contains(&3) && !lines.contains(&2));
+    }
+
+    #[test]
+    fn test_get_random_line_from_minified() {
+        let config = LineConfig::new(Language::JavaScript);
+        let code = format!("const half = value / 2;\n{}\n", "a+b;".repeat(400));
+        assert!(matches!(
+            get_random_line_from(&config, code.as_bytes()),
+            Err(LinesError::NoLines { path: None })
+        ));
+    }
+
+    #[test]
+    fn test_get_random_line_from_no_lines() {
+        let config = LineConfig::new(Language::Java);
+        assert!(get_random_line_from(&config, "// comment\n".as_bytes()).is_err());
+    }
+}
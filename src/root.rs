@@ -0,0 +1,401 @@
+use glob::{glob_with, MatchOptions, Pattern};
+#[cfg(feature = "watch")]
+use ignore::gitignore::Gitignore;
+#[cfg(feature = "watch")]
+use ignore::Match;
+use ignore::{DirEntry, WalkBuilder};
+use std::env;
+use std::ffi::OsStr;
+use std::path::{Component, Path, PathBuf};
+
+use crate::{Language, LinesError, LinesResult};
+
+///
+/// How the paths are matched against the globs of the files, with `*` and `?`
+/// not matching `/`
+///
+pub(crate) const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+///
+/// A folder to take the source files from, with its own globs
+///
+/// The files of all the roots given to
+/// [`LineConfigBuilder::root`](crate::LineConfigBuilder::root) are merged into
+/// one corpus, with the ones of
+/// [`LineConfigBuilder::source`](crate::LineConfigBuilder::source).
+///
+/// ```
+/// use code_lines::{LineConfig, SourceRoot};
+///
+/// let config = LineConfig::builder()
+///     .root(SourceRoot::new("/home/me/.cargo/registry/src"))
+///     .root(
+///         SourceRoot::new("/home/me/monorepo")
+///             .include("services/**/*.rs")
+///             .exclude("**/target/**"),
+///     )
+///     .build()
+///     .unwrap();
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct SourceRoot {
+    folder: String,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    patterns: Vec<Pattern>,
+}
+
+impl SourceRoot {
+    ///
+    /// All the files of the language in the folder and its subfolders
+    ///
+    pub fn new(folder: &str) -> Self {
+        SourceRoot {
+            folder: folder.trim_end_matches('/').to_string(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            patterns: Vec::new(),
+        }
+    }
+
+    ///
+    /// Glob of the files to take, relative to the folder, instead of all the
+    /// files with the extensions of the language
+    ///
+    pub fn include(mut self, glob: &str) -> Self {
+        self.include.push(glob.to_string());
+        self
+    }
+
+    ///
+    /// Glob of the files to leave out, relative to the folder
+    ///
+    pub fn exclude(mut self, glob: &str) -> Self {
+        self.exclude.push(glob.to_string());
+        self
+    }
+
+    ///
+    /// The folder of the files
+    ///
+    pub fn folder(&self) -> &str {
+        &self.folder
+    }
+
+    ///
+    /// The globs of the files left out
+    ///
+    pub(crate) fn exclude_globs(&self) -> &[String] {
+        &self.exclude
+    }
+
+    ///
+    /// Checks the globs to leave out the files
+    /// It returns a [`LinesError`] if one is not valid
+    ///
+    pub(crate) fn compile(mut self) -> LinesResult<Self> {
+        self.patterns = self
+            .exclude
+            .iter()
+            .map(|glob| {
+                Pattern::new(glob).map_err(|source| LinesError::InvalidGlob {
+                    pattern: glob.clone(),
+                    source,
+                })
+            })
+            .collect::<LinesResult<_>>()?;
+        Ok(self)
+    }
+
+    ///
+    /// The globs of the files to take
+    ///
+    pub(crate) fn globs(&self, language: Language) -> Vec<String> {
+        if self.include.is_empty() {
+            folder_globs(&self.folder, language)
+        } else {
+            self.include
+                .iter()
+                .map(|glob| format!("{}/{glob}", self.folder))
+                .collect()
+        }
+    }
+
+    ///
+    /// Whether the file is in the folder and one of the globs leaves it out
+    ///
+    pub(crate) fn excludes(&self, path: &Path) -> bool {
+        path.strip_prefix(&self.folder)
+            .is_ok_and(|relative| self.patterns.iter().any(|p| p.matches_path(relative)))
+    }
+}
+
+///
+/// How the files matching the globs are searched
+///
+#[derive(Debug, Clone)]
+pub(crate) struct Search {
+    exclude: Vec<String>,
+    patterns: Vec<Pattern>,
+    ignore_files: bool,
+}
+
+impl Search {
+    ///
+    /// Leaves out the files matching the glob
+    /// It returns a [`LinesError`] if it is not valid
+    ///
+    pub(crate) fn exclude(&mut self, glob: &str) -> LinesResult<()> {
+        let pattern = Pattern::new(glob).map_err(|source| LinesError::InvalidGlob {
+            pattern: glob.to_string(),
+            source,
+        })?;
+        self.exclude.push(glob.to_string());
+        self.patterns.push(pattern);
+        Ok(())
+    }
+
+    ///
+    /// Whether the files left out by `.gitignore` and `.ignore` files are
+    /// skipped
+    ///
+    pub(crate) fn set_ignore_files(&mut self, ignore_files: bool) {
+        self.ignore_files = ignore_files;
+    }
+
+    ///
+    /// The globs of the files left out and whether the ignore files are used,
+    /// to tell the searches apart
+    ///
+    pub(crate) fn key(&self) -> String {
+        format!("{:?} {}", self.exclude, self.ignore_files)
+    }
+
+    ///
+    /// Whether one of the globs leaves the file out
+    ///
+    pub(crate) fn excludes(&self, path: &Path) -> bool {
+        self.patterns.iter().any(|p| p.matches_path(path))
+    }
+
+    ///
+    /// Whether the ignore files of the folders between the root of the glob
+    /// and the file leave it out, like when the root is walked
+    ///
+    #[cfg(feature = "watch")]
+    pub(crate) fn ignores(&self, root: &Path, path: &Path) -> bool {
+        if !self.ignore_files {
+            return false;
+        }
+        for folder in path.ancestors().skip(1).take_while(|f| f.starts_with(root)) {
+            for name in [".ignore", ".gitignore"] {
+                let (ignore, _) = Gitignore::new(folder.join(name));
+                match ignore.matched_path_or_any_parents(path, false) {
+                    Match::Ignore(_) => return true,
+                    Match::Whitelist(_) => return false,
+                    Match::None => {}
+                }
+            }
+        }
+        false
+    }
+
+    ///
+    /// The files matching the glob that are not left out
+    /// It returns a [`LinesError`] if the glob is not valid
+    ///
+    /// With the ignore files the folder of the glob is walked, as the ignore
+    /// files of every folder inside it have to be read.
+    ///
+    pub(crate) fn find(&self, pattern: &str) -> LinesResult<Vec<PathBuf>> {
+        let invalid = |source| LinesError::InvalidGlob {
+            pattern: pattern.to_string(),
+            source,
+        };
+        let found: Vec<PathBuf> = if self.ignore_files {
+            let matcher = Pattern::new(pattern).map_err(invalid)?;
+            let root = glob_root(pattern);
+            let relative = root.as_os_str().is_empty();
+            WalkBuilder::new(if relative { Path::new(".") } else { &root })
+                .hidden(false)
+                .parents(false)
+                .git_global(false)
+                .git_exclude(false)
+                .require_git(false)
+                .build()
+                .filter_map(Result::ok)
+                .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+                .map(DirEntry::into_path)
+                .map(|path| match relative {
+                    true => path
+                        .strip_prefix(".")
+                        .map_or(path.clone(), Path::to_path_buf),
+                    false => path,
+                })
+                .filter(|path| matcher.matches_path_with(path, MATCH_OPTIONS))
+                .collect()
+        } else {
+            glob_with(pattern, MATCH_OPTIONS)
+                .map_err(invalid)?
+                .filter_map(Result::ok)
+                .filter(|path| path.is_file())
+                .collect()
+        };
+        Ok(found.into_iter().filter(|p| !self.excludes(p)).collect())
+    }
+}
+
+impl Default for Search {
+    fn default() -> Self {
+        Search {
+            exclude: Vec::new(),
+            patterns: Vec::new(),
+            ignore_files: true,
+        }
+    }
+}
+
+///
+/// The folder of the glob before its first wildcard
+///
+pub(crate) fn glob_root(glob: &str) -> PathBuf {
+    Path::new(glob)
+        .components()
+        .take_while(|c| match c {
+            Component::Normal(name) => !name.to_string_lossy().contains(['*', '?', '[']),
+            _ => true,
+        })
+        .collect()
+}
+
+///
+/// The globs of all the files of the language in the folders of the list,
+/// separated by `:`
+///
+pub(crate) fn path_list_globs(folders: &OsStr, language: Language) -> Vec<String> {
+    env::split_paths(folders)
+        .filter(|folder| !folder.as_os_str().is_empty())
+        .flat_map(|folder| folder_globs(&folder.display().to_string(), language))
+        .collect()
+}
+
+///
+/// The globs of all the files of the language in the folder
+///
+pub(crate) fn folder_globs(folder: &str, language: Language) -> Vec<String> {
+    language
+        .spec()
+        .extensions()
+        .iter()
+        .map(|ext| format!("{folder}/**/*.{ext}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::TempFolder;
+    use crate::{LanguageSpec, LineConfig, Syntax};
+
+    struct Roots;
+
+    impl LanguageSpec for Roots {
+        fn name(&self) -> &str {
+            "Roots"
+        }
+
+        fn extensions(&self) -> Vec<String> {
+            vec![String::from("rt")]
+        }
+
+        fn syntax(&self) -> Syntax {
+            Syntax::default()
+        }
+    }
+
+    #[test]
+    fn test_glob_root() {
+        assert_eq!(
+            glob_root("/home/me/.cargo/registry/src/**/*.rs"),
+            PathBuf::from("/home/me/.cargo/registry/src")
+        );
+        assert_eq!(glob_root("src/lib.rs"), PathBuf::from("src/lib.rs"));
+    }
+
+    #[test]
+    fn test_source_roots() {
+        let folder = TempFolder::new("roots");
+        let files = ["a/x.rt", "a/target/y.rt", "b/src/z.rt", "b/w.rt", "c/v.rt"];
+        for file in files {
+            folder.write(file, "let value = 1 + 1;\n");
+        }
+        let path = |file: &str| folder.join(file).display().to_string();
+        let language = Language::register(Roots);
+
+        let config = LineConfig::builder()
+            .language(language)
+            .root(SourceRoot::new(&path("a")).exclude("target/**"))
+            .root(SourceRoot::new(&path("b")).include("src/**/*.rt"))
+            .source(&path("c/*.rt"))
+            .build()
+            .unwrap();
+        assert_eq!(
+            config.globbed_paths().unwrap(),
+            vec![path("a/x.rt"), path("b/src/z.rt"), path("c/v.rt")]
+        );
+
+        let folders = format!("{}::{}", path("a"), path("c"));
+        assert_eq!(
+            path_list_globs(OsStr::new(&folders), language),
+            vec![path("a/**/*.rt"), path("c/**/*.rt")]
+        );
+
+        let root = SourceRoot::new(&path("a")).exclude("[");
+        assert!(LineConfig::builder().root(root).build().is_err());
+    }
+
+    #[test]
+    fn test_search_ignore_files() {
+        let home = TempFolder::new("ignore");
+        let folder = home.join("project");
+        let files = ["src/a.rs", "src/b.tmp.rs", "generated/c.rs", "vendor/d.rs"];
+        for file in files {
+            home.write(&format!("project/{file}"), "let value = 1 + 1;\n");
+        }
+        // The ignore files above the folder of the glob are not read
+        home.write(".gitignore", "*\n");
+        home.write("project/.gitignore", "generated/\n");
+        home.write("project/src/.ignore", "*.tmp.rs\n");
+        let path = |file: &str| folder.join(file).display().to_string();
+        let glob = format!("{}/**/*.rs", folder.display());
+
+        let config = LineConfig::builder()
+            .source(&glob)
+            .exclude_files("**/vendor/**")
+            .build()
+            .unwrap();
+        assert_eq!(config.globbed_paths().unwrap(), vec![path("src/a.rs")]);
+
+        let config = LineConfig::builder()
+            .source(&glob)
+            .exclude_files("**/vendor/**")
+            .ignore_files(false)
+            .build()
+            .unwrap();
+        assert_eq!(
+            config.globbed_paths().unwrap(),
+            vec![
+                path("generated/c.rs"),
+                path("src/a.rs"),
+                path("src/b.tmp.rs")
+            ]
+        );
+
+        assert!(LineConfig::builder().exclude_files("[").build().is_err());
+    }
+}
@@ -0,0 +1,157 @@
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::archive;
+use crate::scan::scan;
+use crate::{get_lines_from_file, LineConfig};
+
+///
+/// How the random lines are picked
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Sampling {
+    ///
+    /// Every line has the same chance, so long files are picked more often
+    /// than short ones
+    ///
+    /// All the files are read to count their lines.
+    ///
+    Lines,
+    ///
+    /// Every file has the same chance, and then every line in it
+    ///
+    #[default]
+    Files,
+    ///
+    /// Every package, like a crate, has the same chance, then every file in
+    /// it, and then every line
+    ///
+    /// See [`LanguageSpec::package`](crate::LanguageSpec::package).
+    ///
+    Packages,
+}
+
+///
+/// The files the lines are picked from, with what the sampling needs of them
+///
+#[derive(Clone)]
+pub(crate) struct Candidates {
+    paths: Vec<String>,
+    counts: Vec<usize>,
+    packages: Vec<String>,
+}
+
+impl Candidates {
+    pub(crate) fn new(config: &LineConfig, paths: Vec<String>) -> Self {
+        let counts = match config.sampling() {
+            Sampling::Lines => match config.cached(|index| Ok(index.counts(&paths))) {
+                Some(Ok(counts)) => counts,
+                _ => scan(config, &paths, |path| {
+                    let lines = archive::open(path).map(get_lines_from_file);
+                    config.eligible_lines(&lines.unwrap_or_default()).len()
+                }),
+            },
+            _ => Vec::new(),
+        };
+        let packages = match config.sampling() {
+            Sampling::Packages => paths
+                .iter()
+                .map(|path| config.language.spec().package(path))
+                .collect(),
+            _ => Vec::new(),
+        };
+        Candidates {
+            paths,
+            counts,
+            packages,
+        }
+    }
+
+    pub(crate) fn path(&self, position: usize) -> &str {
+        &self.paths[position]
+    }
+
+    ///
+    /// The position of a random file, none if no file has lines left
+    ///
+    pub(crate) fn pick(&self, config: &LineConfig) -> Option<usize> {
+        if self.paths.is_empty() {
+            return None;
+        }
+        let mut rng = config.rng();
+        match config.sampling() {
+            Sampling::Lines => WeightedIndex::new(&self.counts)
+                .ok()
+                .map(|weights| weights.sample(&mut *rng)),
+            Sampling::Files => Some(rng.gen_range(0..self.paths.len())),
+            Sampling::Packages => {
+                let mut packages: Vec<&String> = self.packages.iter().collect();
+                packages.sort();
+                packages.dedup();
+                let package = packages[rng.gen_range(0..packages.len())];
+                let positions: Vec<usize> = (0..self.paths.len())
+                    .filter(|&i| &self.packages[i] == package)
+                    .collect();
+                positions.choose(&mut *rng).copied()
+            }
+        }
+    }
+
+    ///
+    /// Updates the number of lines left in the file
+    ///
+    pub(crate) fn set_count(&mut self, position: usize, count: usize) {
+        if let Some(current) = self.counts.get_mut(position) {
+            *current = count;
+        }
+    }
+
+    ///
+    /// Removes the file, moving the last one to its position
+    ///
+    pub(crate) fn remove(&mut self, position: usize) -> String {
+        if !self.counts.is_empty() {
+            self.counts.swap_remove(position);
+        }
+        if !self.packages.is_empty() {
+            self.packages.swap_remove(position);
+        }
+        self.paths.swap_remove(position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::TempFolder;
+
+    #[test]
+    fn test_candidates_lines() {
+        let folder = TempFolder::new("sampling");
+        let paths: Vec<String> = [("long.rs", 3), ("short.rs", 1), ("empty.rs", 0)]
+            .iter()
+            .map(|(name, count)| {
+                let code: String = (0..*count)
+                    .map(|i| format!("let value = {i} + {i};\n"))
+                    .collect();
+                folder.write(name, code)
+            })
+            .collect();
+
+        let config = LineConfig::builder()
+            .sampling(Sampling::Lines)
+            .build()
+            .unwrap();
+        let mut candidates = Candidates::new(&config, paths);
+        assert_eq!(candidates.counts, vec![3, 1, 0]);
+        let picks: Vec<usize> = (0..100).filter_map(|_| candidates.pick(&config)).collect();
+        assert_eq!(picks.len(), 100);
+        assert!(!picks.contains(&2));
+        assert!(picks.iter().filter(|&&p| p == 0).count() > 50);
+
+        candidates.set_count(0, 0);
+        candidates.remove(1);
+        assert_eq!(candidates.pick(&config), None);
+    }
+}
@@ -0,0 +1,48 @@
+use crate::LineConfig;
+
+///
+/// Runs the function on every path, in parallel with the `parallel` feature
+///
+/// The results are in the order of the paths, so the lines picked from them
+/// with a seed are always the same.
+///
+#[cfg(feature = "parallel")]
+pub(crate) fn scan<T, F>(config: &LineConfig, paths: &[String], f: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(&str) -> T + Sync,
+{
+    use rayon::prelude::*;
+
+    let run = || paths.par_iter().map(|path| f(path)).collect();
+    match config.threads() {
+        Some(threads) => match rayon::ThreadPoolBuilder::new().num_threads(threads).build() {
+            Ok(pool) => pool.install(run),
+            Err(_) => run(),
+        },
+        None => run(),
+    }
+}
+
+///
+/// Runs the function on every path, in parallel with the `parallel` feature
+///
+#[cfg(not(feature = "parallel"))]
+pub(crate) fn scan<T, F>(_config: &LineConfig, paths: &[String], f: F) -> Vec<T>
+where
+    F: Fn(&str) -> T,
+{
+    paths.iter().map(|path| f(path)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_order() {
+        let paths: Vec<String> = (0..1000).map(|i| i.to_string()).collect();
+        let config = LineConfig::builder().threads(4).build().unwrap();
+        assert_eq!(scan(&config, &paths, |p| p.to_string()), paths);
+    }
+}
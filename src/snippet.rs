@@ -0,0 +1,315 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::archive;
+use crate::lexer::{Lexer, LineKind};
+use crate::{get_lines_from_file, in_random_file, Language, LineConfig, LinesError, LinesResult};
+
+///
+/// Returns a random snippet of consecutive lines of code from the same file
+/// It returns a [`LinesError`] if something bad happens
+///
+/// The snippet starts with a line [`get_random_line`](crate::get_random_line)
+/// could return, it has no comment-only lines, and its indentation is kept
+/// relative to its least indented line.
+///
+/// The files without such a snippet are skipped like the files without lines
+/// of [`get_random_code_line`](crate::get_random_code_line), an error is only
+/// returned if none has one.
+///
+/// # Arguments
+///
+/// * `config` - A reference to a [`LineConfig`]
+/// * `min_lines` - The minimum number of lines of the snippet, at least 1
+/// * `max_lines` - The maximum number of lines of the snippet
+///
+pub fn get_random_snippet(
+    config: &LineConfig,
+    min_lines: usize,
+    max_lines: usize,
+) -> LinesResult<String> {
+    if min_lines == 0 || min_lines > max_lines {
+        return Err(LinesError::InvalidSnippetLength {
+            min_lines,
+            max_lines,
+        });
+    }
+    let snippet = in_random_file(config, false, |path| {
+        let lines = match archive::open(path) {
+            Ok(file) => get_lines_from_file(file),
+            Err(e) => return Err(LinesError::io(path, e)),
+        };
+
+        let mut rng = config.rng();
+        let chosen = rng.gen_range(min_lines..=max_lines);
+        let lengths = std::iter::once(chosen).chain((min_lines..=max_lines).rev());
+        for len in lengths {
+            if let Some(&start) = snippet_starts(config, &lines, len).choose(&mut *rng) {
+                return Ok(Some(dedent(&lines[start..start + len])));
+            }
+        }
+        Ok(None)
+    });
+    match snippet {
+        Ok(Some(snippet)) => Ok(snippet),
+        Ok(None) | Err(LinesError::NoLines { path: None }) => Err(LinesError::NoSnippet {
+            min_lines,
+            max_lines,
+        }),
+        Err(e) => Err(e),
+    }
+}
+
+///
+/// Returns a random complete item, like a function, a method, an `impl` block
+/// or a class, from a random file
+/// It returns a [`LinesError`] if something bad happens
+///
+/// The item goes from the line starting it to the one closing its braces,
+/// with its indentation kept relative to its least indented line. Only the
+/// languages that tell where their items start, with
+/// [`LanguageSpec::starts_item`](crate::LanguageSpec::starts_item), have them.
+///
+/// The files without such an item are skipped, an error is only returned if
+/// none has one.
+///
+/// # Arguments
+///
+/// * `config` - A reference to a [`LineConfig`]
+/// * `max_lines` - The maximum number of lines of the item, longer items are
+///   skipped
+///
+pub fn get_random_item(config: &LineConfig, max_lines: usize) -> LinesResult<String> {
+    let item = in_random_file(config, false, |path| {
+        let lines = match archive::open(path) {
+            Ok(file) => get_lines_from_file(file),
+            Err(e) => return Err(LinesError::io(path, e)),
+        };
+
+        let items = item_ranges(config.language, &lines, max_lines);
+        Ok(items
+            .choose(&mut *config.rng())
+            .map(|&(start, end)| dedent(&lines[start..=end])))
+    });
+    match item {
+        Ok(Some(item)) => Ok(item),
+        Ok(None) | Err(LinesError::NoLines { path: None }) => Err(LinesError::NoItem { max_lines }),
+        Err(e) => Err(e),
+    }
+}
+
+///
+/// The first and last lines of the items of at most `max_lines` lines
+///
+fn item_ranges(language: Language, lines: &[String], max_lines: usize) -> Vec<(usize, usize)> {
+    let syntax = language.spec().syntax();
+    let mut lexer = Lexer::new(&syntax);
+    let code: Vec<String> = lines.iter().map(|l| lexer.code(l).1).collect();
+    (0..lines.len())
+        .filter(|&start| language.spec().starts_item(&code[start]))
+        .filter_map(|start| {
+            item_end(&code[start..lines.len().min(start + max_lines)])
+                .map(|len| (start, start + len))
+        })
+        .collect()
+}
+
+///
+/// The index of the line closing the braces opened by the item at the start
+/// of the code, if it is there
+///
+fn item_end(code: &[String]) -> Option<usize> {
+    let mut depth = 0;
+    let mut opened = false;
+    for (i, line) in code.iter().enumerate() {
+        for c in line.chars() {
+            match c {
+                '{' => {
+                    depth += 1;
+                    opened = true;
+                }
+                '}' if opened => depth -= 1,
+                ';' if !opened => return None,
+                _ => {}
+            }
+            if opened && depth <= 0 {
+                return Some(i);
+            }
+        }
+    }
+    None
+}
+
+///
+/// The lines where a snippet of `len` lines can start
+///
+fn snippet_starts(config: &LineConfig, lines: &[String], len: usize) -> Vec<usize> {
+    let syntax = config.language.spec().syntax();
+    let mut lexer = Lexer::new(&syntax);
+    let kinds: Vec<LineKind> = lines.iter().map(|l| lexer.line(l)).collect();
+    config
+        .eligible_lines(lines)
+        .into_iter()
+        .filter(|&start| {
+            start + len <= lines.len()
+                && kinds[start + len - 1] == LineKind::Code
+                && kinds[start..start + len]
+                    .iter()
+                    .all(|k| matches!(k, LineKind::Code | LineKind::Blank))
+        })
+        .collect()
+}
+
+///
+/// Joins the lines removing the indentation they all share
+///
+pub(crate) fn dedent(lines: &[String]) -> String {
+    let indentation = lines
+        .iter()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| &l[..l.len() - l.trim_start().len()])
+        .reduce(|common, indentation| {
+            let shared = common
+                .char_indices()
+                .zip(indentation.chars())
+                .find(|((_, a), b)| a != b)
+                .map_or(common.len().min(indentation.len()), |((i, _), _)| i);
+            &common[..shared]
+        })
+        .unwrap_or("");
+    lines
+        .iter()
+        .map(|l| l.strip_prefix(indentation).unwrap_or("").trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::TempFolder;
+    use crate::get_random_line;
+    use std::fs;
+
+    fn get_lines() -> Vec<String> {
+        vec![
+            "    fn half(value: u32) -> u32 {".to_string(),
+            "        // the half".to_string(),
+            "        value / 2".to_string(),
+            "    }".to_string(),
+            "".to_string(),
+            "    fn double(value: u32) -> u32 {".to_string(),
+            "        value * 2".to_string(),
+            "    }".to_string(),
+        ]
+    }
+
+    #[test]
+    fn test_snippet_starts() {
+        assert_eq!(
+            snippet_starts(&LineConfig::default(), &get_lines(), 3),
+            vec![5]
+        );
+        assert_eq!(
+            snippet_starts(&LineConfig::default(), &get_lines(), 2),
+            vec![2, 5, 6]
+        );
+        assert!(snippet_starts(&LineConfig::default(), &get_lines(), 9).is_empty());
+    }
+
+    #[test]
+    fn test_get_random_snippet_skips_files() {
+        let folder = TempFolder::new("snippet");
+        for i in 0..5 {
+            folder.write(&format!("mod{i}.rs"), "pub mod thing;\n");
+        }
+        let code = "let first = 1 + 1;\nlet second = 2 + 2;\n";
+        let lib = folder.write("lib.rs", code);
+        let config = LineConfig::builder()
+            .source(&format!("{}/*.rs", folder.display()))
+            .retries(0)
+            .build()
+            .unwrap();
+        for _ in 0..5 {
+            assert_eq!(get_random_snippet(&config, 2, 2).unwrap(), code.trim_end());
+        }
+
+        fs::remove_file(lib).unwrap();
+        assert!(matches!(
+            get_random_snippet(&config, 2, 2),
+            Err(LinesError::NoSnippet { .. })
+        ));
+        assert_eq!(get_random_line(&config).unwrap(), "pub mod thing;");
+    }
+
+    #[test]
+    fn test_get_random_item_skips_files() {
+        let folder = TempFolder::new("item");
+        for i in 0..5 {
+            folder.write(&format!("mod{i}.rs"), "pub mod thing;\n");
+        }
+        let code = "fn half(value: u32) -> u32 {\n    value / 2\n}\n";
+        let lib = folder.write("lib.rs", code);
+        let config = LineConfig::builder()
+            .source(&format!("{}/*.rs", folder.display()))
+            .retries(0)
+            .build()
+            .unwrap();
+        for _ in 0..5 {
+            assert_eq!(get_random_item(&config, 10).unwrap(), code.trim_end());
+        }
+
+        fs::remove_file(lib).unwrap();
+        assert!(matches!(
+            get_random_item(&config, 10),
+            Err(LinesError::NoItem { max_lines: 10 })
+        ));
+    }
+
+    #[test]
+    fn test_item_ranges() {
+        let mut lines = get_lines();
+        lines.insert(0, "    fn declared(value: u32);".to_string());
+        lines.insert(2, "        let text = \"}\"; // }".to_string());
+        assert_eq!(
+            item_ranges(Language::Rust, &lines, 20),
+            vec![(1, 5), (7, 9)]
+        );
+        assert_eq!(item_ranges(Language::Rust, &lines, 3), vec![(7, 9)]);
+    }
+
+    #[test]
+    fn test_item_ranges_java() {
+        let lines = vec![
+            "public class Thing {".to_string(),
+            "    @Override".to_string(),
+            "    public String toString() {".to_string(),
+            "        return format(\"{}\");".to_string(),
+            "    }".to_string(),
+            "    void check(boolean a, boolean b) {".to_string(),
+            "        if (a) {".to_string(),
+            "            run();".to_string(),
+            "        } else if (b) {".to_string(),
+            "            try {".to_string(),
+            "                run();".to_string(),
+            "            } catch (Exception e) {".to_string(),
+            "                stop();".to_string(),
+            "            }".to_string(),
+            "        }".to_string(),
+            "    }".to_string(),
+            "}".to_string(),
+        ];
+        assert_eq!(
+            item_ranges(Language::Java, &lines, 20),
+            vec![(0, 16), (2, 4), (5, 15)]
+        );
+    }
+
+    #[test]
+    fn test_dedent() {
+        assert_eq!(
+            dedent(&get_lines()[3..8]),
+            "}\n\nfn double(value: u32) -> u32 {\n    value * 2\n}"
+        );
+    }
+}
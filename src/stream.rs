@@ -0,0 +1,113 @@
+use crate::batch::Sampler;
+use crate::sampling::Candidates;
+use crate::{CodeLine, LineConfig, LinesError, LinesResult};
+
+///
+/// Endless source of random lines of code that match a config
+///
+/// The files are searched once, when the stream is created, and the lines are
+/// read from them as they are needed. No line is returned twice until all of
+/// them have been, then the stream ends, or starts again in a new order with
+/// [`LineStream::reshuffle`].
+///
+/// ```no_run
+/// use code_lines::{LineConfig, LineStream};
+///
+/// let config = LineConfig::default();
+/// for line in LineStream::new(&config)?.reshuffle(true).take(100) {
+///     println!("{}", line?);
+/// }
+/// # Ok::<(), code_lines::LinesError>(())
+/// ```
+///
+pub struct LineStream<'a> {
+    config: &'a LineConfig,
+    candidates: Candidates,
+    sampler: Sampler<'a>,
+    reshuffle: bool,
+    returned: bool,
+    failed: bool,
+}
+
+impl<'a> LineStream<'a> {
+    ///
+    /// Stream of the lines that match the config argument
+    /// It returns a [`LinesError`] if the files can't be searched
+    ///
+    pub fn new(config: &'a LineConfig) -> LinesResult<Self> {
+        let candidates = Candidates::new(config, config.paths()?);
+        Ok(LineStream {
+            config,
+            sampler: Sampler::new(config, candidates.clone()),
+            candidates,
+            reshuffle: false,
+            returned: false,
+            failed: false,
+        })
+    }
+
+    ///
+    /// Whether to start again in a new order once all the lines have been
+    /// returned, false by default
+    ///
+    pub fn reshuffle(mut self, reshuffle: bool) -> Self {
+        self.reshuffle = reshuffle;
+        self
+    }
+}
+
+impl Iterator for LineStream<'_> {
+    type Item = LinesResult<CodeLine>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        if let Some(line) = self.sampler.next_line() {
+            self.returned = true;
+            return Some(Ok(line));
+        }
+        if !self.returned {
+            self.failed = true;
+            return Some(Err(LinesError::NoLines { path: None }));
+        }
+        if !self.reshuffle {
+            return None;
+        }
+        self.sampler = Sampler::new(self.config, self.candidates.clone());
+        self.returned = false;
+        self.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::TempFolder;
+
+    #[test]
+    fn test_line_stream() {
+        let folder = TempFolder::new("stream");
+        let path = folder.write("a.rs", "let first = 1 + 1;\nlet second = 2 + 2;\n");
+        let config = LineConfig::builder().source(&path).build().unwrap();
+
+        let stream = LineStream::new(&config).unwrap();
+        assert_eq!(stream.filter_map(Result::ok).count(), 2);
+
+        let stream = LineStream::new(&config).unwrap().reshuffle(true);
+        let mut lines: Vec<String> = stream.take(4).map(|l| l.unwrap().text).collect();
+        lines[..2].sort();
+        lines[2..].sort();
+        assert_eq!(lines[..2], lines[2..]);
+    }
+
+    #[test]
+    fn test_line_stream_no_lines() {
+        let folder = TempFolder::new("stream-no-lines");
+        let path = folder.write("b.rs", "// only a comment here\n");
+        let config = LineConfig::builder().source(&path).build().unwrap();
+        let mut stream = LineStream::new(&config).unwrap().reshuffle(true);
+        assert!(stream.next().unwrap().is_err());
+        assert!(stream.next().is_none());
+    }
+}
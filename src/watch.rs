@@ -0,0 +1,169 @@
+use glob::{glob, Pattern};
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+
+use crate::archive;
+use crate::root::{glob_root, MATCH_OPTIONS};
+use crate::{LineConfig, LinesError, LinesResult};
+
+///
+/// Keeps the index of a configuration up to date while it lives
+///
+/// The folders of the globs of the configuration are watched, and the files
+/// that are added, changed or removed in them are updated in the index
+/// without searching all the files again. The configuration needs a cache, see
+/// [`LineConfigBuilder::cache`](crate::LineConfigBuilder::cache).
+///
+/// ```no_run
+/// use code_lines::{get_random_line, IndexWatcher, LineConfig};
+///
+/// let config = LineConfig::builder().cache(true).build()?;
+/// let _watcher = IndexWatcher::new(&config)?;
+/// loop {
+///     println!("{}", get_random_line(&config)?);
+///     # break;
+/// }
+/// # Ok::<(), code_lines::LinesError>(())
+/// ```
+///
+pub struct IndexWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+impl IndexWatcher {
+    ///
+    /// Starts watching the files of the configuration
+    /// It returns a [`LinesError`] if the configuration has no cache or the
+    /// folders can't be watched
+    ///
+    pub fn new(config: &LineConfig) -> LinesResult<Self> {
+        config.cached(|_| Ok(())).ok_or(LinesError::NoCache)??;
+
+        let globs = config.globs();
+        let patterns: Vec<(PathBuf, Pattern)> = globs
+            .iter()
+            .filter_map(|g| Some((glob_root(g), Pattern::new(g).ok()?)))
+            .collect();
+        let mut roots: Vec<PathBuf> = patterns.iter().map(|(root, _)| root.clone()).collect();
+        roots.sort();
+        roots.dedup();
+        let watched = config.clone();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            let mut changed = Vec::new();
+            for path in event.paths {
+                if path.is_dir() {
+                    for file in files_in(&path).filter(|p| matches(&watched, &patterns, p)) {
+                        changed.extend(expand(&watched, file));
+                    }
+                } else if matches(&watched, &patterns, &path) {
+                    changed.extend(expand(&watched, path));
+                } else if !path.exists() {
+                    changed.push(path);
+                }
+            }
+            if !changed.is_empty() {
+                let changed: Vec<String> =
+                    changed.iter().map(|p| p.display().to_string()).collect();
+                watched.cached(|index| {
+                    index.refresh(&watched, &changed);
+                    Ok(())
+                });
+            }
+        })
+        .map_err(|source| LinesError::Watch { path: None, source })?;
+
+        for root in roots.iter().filter(|r| r.is_dir()) {
+            watcher
+                .watch(root, RecursiveMode::Recursive)
+                .map_err(|source| LinesError::Watch {
+                    path: Some(root.display().to_string()),
+                    source,
+                })?;
+        }
+        Ok(IndexWatcher { _watcher: watcher })
+    }
+}
+
+///
+/// Whether the file is one of the configuration, matching one of the globs
+/// without being left out by the ignore files in its root
+///
+fn matches(config: &LineConfig, patterns: &[(PathBuf, Pattern)], path: &Path) -> bool {
+    path.is_file()
+        && patterns.iter().any(|(root, p)| {
+            p.matches_path_with(path, MATCH_OPTIONS) && !config.ignores(root, path)
+        })
+        && !config.excludes(path)
+        && config
+            .language()
+            .spec()
+            .accepts_path(&path.display().to_string())
+}
+
+///
+/// The files inside the archive, or the file itself if it is not one
+///
+fn expand(config: &LineConfig, path: PathBuf) -> Vec<PathBuf> {
+    if archive::is_archive(&path) {
+        let extensions = config.language().spec().extensions();
+        archive::entries(&path, &extensions).unwrap_or_default()
+    } else {
+        vec![path]
+    }
+}
+
+///
+/// The files in the folder and its subfolders
+///
+fn files_in(folder: &Path) -> impl Iterator<Item = PathBuf> {
+    glob(&format!(
+        "{}/**/*",
+        Pattern::escape(&folder.display().to_string())
+    ))
+    .into_iter()
+    .flatten()
+    .filter_map(Result::ok)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::TempFolder;
+    use std::time::{Duration, Instant};
+    use std::{fs, thread};
+
+    #[test]
+    fn test_index_watcher() {
+        let folder = TempFolder::new("watch");
+        let old = folder.write("sources/old.rs", "let old = 1 + 1;\n");
+        folder.write("sources/.gitignore", "generated/\n");
+        let sources = folder.join("sources");
+        let flat = folder.join("flat");
+        fs::create_dir_all(&flat).unwrap();
+        let config = LineConfig::builder()
+            .source(&format!("{}/**/*.rs", sources.display()))
+            .source(&format!("{}/*.rs", flat.display()))
+            .cache_folder(&folder.join("cache").display().to_string())
+            .build()
+            .unwrap();
+        let watcher = IndexWatcher::new(&config).unwrap();
+
+        // Only the files right in the folder match its `*.rs`
+        folder.write("flat/sub/deep.rs", "let deep = 3 + 3;\n");
+        folder.write("sources/generated/bindings.rs", "let bound = 1 + 1;\n");
+        let new = folder.write("sources/nested/new.rs", "let new = 2 + 2;\n");
+        fs::remove_file(old).unwrap();
+
+        let expected = vec![new];
+        let start = Instant::now();
+        while config.paths().unwrap() != expected && start.elapsed() < Duration::from_secs(5) {
+            thread::sleep(Duration::from_millis(20));
+        }
+        assert_eq!(config.paths().unwrap(), expected);
+        // Stop the events before the folder is removed
+        drop(watcher);
+    }
+}